@@ -0,0 +1,262 @@
+//! Easing
+//!
+//! The shape of a transition from `0.` to `1.`, shared by tweens, movement and effects.
+//! `In` curves start slow, `Out` curves end slow, `InOut` curves do both.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts and ends slow, cheaper than [`Easing::SineInOut`].
+    SmoothStep,
+    QuadraticIn,
+    QuadraticOut,
+    QuadraticInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuarticIn,
+    QuarticOut,
+    QuarticInOut,
+    QuinticIn,
+    QuinticOut,
+    QuinticInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExponentialIn,
+    ExponentialOut,
+    ExponentialInOut,
+    CircularIn,
+    CircularOut,
+    CircularInOut,
+    /// Goes slightly below `0.` first.
+    BackIn,
+    /// Overshoots `1.` then settles.
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    /// Wobbles around `1.`.
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    /// Bounces on `1.` like a dropped ball.
+    BounceOut,
+    BounceInOut,
+}
+
+#[derive(Clone, Copy)]
+enum Curve {
+    Quadratic,
+    Cubic,
+    Quartic,
+    Quintic,
+    Sine,
+    Exponential,
+    Circular,
+    Back,
+    Elastic,
+    Bounce,
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    In,
+    Out,
+    InOut,
+}
+
+impl Curve {
+    /// The `In` version.
+    fn ease_in(self, t: f32) -> f32 {
+        match self {
+            Curve::Quadratic => t * t,
+            Curve::Cubic => t * t * t,
+            Curve::Quartic => t * t * t * t,
+            Curve::Quintic => t * t * t * t * t,
+            Curve::Sine => 1. - (t * PI / 2.).cos(),
+            Curve::Exponential => {
+                if t <= 0. {
+                    0.
+                } else {
+                    2_f32.powf(10. * t - 10.)
+                }
+            }
+            Curve::Circular => 1. - (1. - t * t).max(0.).sqrt(),
+            Curve::Back => {
+                const C1: f32 = 1.70158;
+                (C1 + 1.) * t * t * t - C1 * t * t
+            }
+            Curve::Elastic => {
+                if t <= 0. || t >= 1. {
+                    t
+                } else {
+                    -(2_f32.powf(10. * t - 10.)) * ((10. * t - 10.75) * 2. * PI / 3.).sin()
+                }
+            }
+            Curve::Bounce => 1. - bounce_out(1. - t),
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+
+    if t < 1. / D1 {
+        N1 * t * t
+    } else if t < 2. / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+impl Easing {
+    pub const ALL: [Easing; 32] = [
+        Easing::Linear,
+        Easing::SmoothStep,
+        Easing::QuadraticIn,
+        Easing::QuadraticOut,
+        Easing::QuadraticInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::QuarticIn,
+        Easing::QuarticOut,
+        Easing::QuarticInOut,
+        Easing::QuinticIn,
+        Easing::QuinticOut,
+        Easing::QuinticInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::ExponentialIn,
+        Easing::ExponentialOut,
+        Easing::ExponentialInOut,
+        Easing::CircularIn,
+        Easing::CircularOut,
+        Easing::CircularInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::ElasticInOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::BounceInOut,
+    ];
+
+    /// `t` in `[0; 1]`
+    ///
+    /// Back and elastic curves leave `[0; 1]` on the way.
+    pub fn ease(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        let (curve, mode) = match self {
+            Easing::Linear => return t,
+            Easing::SmoothStep => return t * t * (3. - 2. * t),
+            Easing::QuadraticIn => (Curve::Quadratic, Mode::In),
+            Easing::QuadraticOut => (Curve::Quadratic, Mode::Out),
+            Easing::QuadraticInOut => (Curve::Quadratic, Mode::InOut),
+            Easing::CubicIn => (Curve::Cubic, Mode::In),
+            Easing::CubicOut => (Curve::Cubic, Mode::Out),
+            Easing::CubicInOut => (Curve::Cubic, Mode::InOut),
+            Easing::QuarticIn => (Curve::Quartic, Mode::In),
+            Easing::QuarticOut => (Curve::Quartic, Mode::Out),
+            Easing::QuarticInOut => (Curve::Quartic, Mode::InOut),
+            Easing::QuinticIn => (Curve::Quintic, Mode::In),
+            Easing::QuinticOut => (Curve::Quintic, Mode::Out),
+            Easing::QuinticInOut => (Curve::Quintic, Mode::InOut),
+            Easing::SineIn => (Curve::Sine, Mode::In),
+            Easing::SineOut => (Curve::Sine, Mode::Out),
+            Easing::SineInOut => (Curve::Sine, Mode::InOut),
+            Easing::ExponentialIn => (Curve::Exponential, Mode::In),
+            Easing::ExponentialOut => (Curve::Exponential, Mode::Out),
+            Easing::ExponentialInOut => (Curve::Exponential, Mode::InOut),
+            Easing::CircularIn => (Curve::Circular, Mode::In),
+            Easing::CircularOut => (Curve::Circular, Mode::Out),
+            Easing::CircularInOut => (Curve::Circular, Mode::InOut),
+            Easing::BackIn => (Curve::Back, Mode::In),
+            Easing::BackOut => (Curve::Back, Mode::Out),
+            Easing::BackInOut => (Curve::Back, Mode::InOut),
+            Easing::ElasticIn => (Curve::Elastic, Mode::In),
+            Easing::ElasticOut => (Curve::Elastic, Mode::Out),
+            Easing::ElasticInOut => (Curve::Elastic, Mode::InOut),
+            Easing::BounceIn => (Curve::Bounce, Mode::In),
+            Easing::BounceOut => (Curve::Bounce, Mode::Out),
+            Easing::BounceInOut => (Curve::Bounce, Mode::InOut),
+        };
+
+        match mode {
+            Mode::In => curve.ease_in(t),
+            Mode::Out => 1. - curve.ease_in(1. - t),
+            Mode::InOut if t < 0.5 => curve.ease_in(2. * t) / 2.,
+            Mode::InOut => 1. - curve.ease_in(2. - 2. * t) / 2.,
+        }
+    }
+
+    /// The `t` at which [`Easing::ease`] reaches `value`.
+    ///
+    /// Found by bisection for most curves: on the ones leaving `[0; 1]`,
+    /// it is one of the matching points.
+    pub fn inverse(self, value: f32) -> f32 {
+        let value = value.clamp(0., 1.);
+        match self {
+            Easing::Linear => value,
+            Easing::QuadraticIn => value.sqrt(),
+            Easing::QuadraticOut => 1. - (1. - value).sqrt(),
+            Easing::SmoothStep => 0.5 - ((1. - 2. * value).asin() / 3.).sin(),
+            _ => {
+                let (mut low, mut high) = (0., 1.);
+                for _ in 0..24 {
+                    let middle = (low + high) / 2.;
+                    if self.ease(middle) < value {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+                (low + high) / 2.
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_curve_goes_from_zero_to_one() {
+        for easing in Easing::ALL {
+            assert!(
+                easing.ease(0.).abs() < 1e-5,
+                "{easing:?} starts at {}",
+                easing.ease(0.)
+            );
+            assert!(
+                (easing.ease(1.) - 1.).abs() < 1e-5,
+                "{easing:?} ends at {}",
+                easing.ease(1.)
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_is_clamped() {
+        for easing in Easing::ALL {
+            assert_eq!(easing.ease(-1.), easing.ease(0.), "{easing:?}");
+            assert_eq!(easing.ease(2.), easing.ease(1.), "{easing:?}");
+        }
+    }
+}
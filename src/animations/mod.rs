@@ -1,20 +1,34 @@
 use bevy::prelude::*;
 
+use crate::juice::{juice_enabled, JuiceEffect};
+
+pub mod easing;
 pub mod sprite_sheet_animation;
+pub mod tween;
 
 pub struct AnimationPlugin;
 
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CharacterSpriteSheet>().add_systems(
-            PostUpdate,
-            (
-                sprite_sheet_animation::animate_sprite_sheet,
-                sprite_sheet_animation::jump_frame_character_state,
-                sprite_sheet_animation::tempo_animation_timer,
-                sprite_sheet_animation::animate_character,
-            ),
-        );
+        app.add_plugins(tween::TweenPlugin)
+            .init_resource::<CharacterSpriteSheet>()
+            .add_event::<sprite_sheet_animation::AnimationEvent>()
+            .add_event::<sprite_sheet_animation::AnimationStarted>()
+            .add_event::<sprite_sheet_animation::AnimationFinished>()
+            .add_systems(
+                PostUpdate,
+                (
+                    sprite_sheet_animation::animate_sprite_sheet,
+                    sprite_sheet_animation::jump_frame_character_state,
+                    sprite_sheet_animation::randomize_idle_tempo
+                        .run_if(juice_enabled(JuiceEffect::IdleTempo))
+                        .after(sprite_sheet_animation::jump_frame_character_state),
+                    sprite_sheet_animation::tempo_animation_timer,
+                    // don't step a new state's animation from the previous state's frame
+                    sprite_sheet_animation::animate_character
+                        .after(sprite_sheet_animation::jump_frame_character_state),
+                ),
+            );
     }
 }
 
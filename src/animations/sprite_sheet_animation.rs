@@ -3,13 +3,30 @@ use bevy::prelude::*;
 use rand::Rng;
 use std::{collections::HashMap, time::Duration};
 
-use crate::{characters::player::Player, constants::FRAME_TIME};
+use crate::{
+    constants::FRAME_TIME,
+    hit_stop::{Frozen, GameTime},
+    juice::JuiceSettings,
+    replay::GameRng,
+};
 
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Component)]
 pub enum CharacterState {
     #[default]
     Idle,
     Run,
+    Shoot,
+    Dash,
+    Melee,
+    Hit,
+    Death,
+}
+
+impl CharacterState {
+    /// These animations hold their last frame instead of moving to a next state.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, CharacterState::Death)
+    }
 }
 
 #[derive(Deref, DerefMut, Component)]
@@ -46,13 +63,92 @@ pub enum AnimationDuration {
 #[derive(Deref, DerefMut, Reflect, Component)]
 pub struct TempoAnimation(pub Timer);
 
+/// Gameplay moments marked on animation frames.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect)]
+pub enum AnimationTag {
+    /// A foot touches the ground.
+    Footstep,
+    /// The weapon goes off.
+    MuzzleFlash,
+    /// The melee hitbox becomes active.
+    HitboxStart,
+    /// The melee hitbox stops being active.
+    HitboxEnd,
+}
+
+/// Animation notifies: for a CharacterState, the tags to fire on some of its frames.
+///
+/// Frames are counted from the first frame of the state's animation.
+#[derive(Deref, DerefMut, Clone, Reflect, Default, Component)]
+pub struct AnimationNotifies(pub HashMap<CharacterState, Vec<(usize, AnimationTag)>>);
+
+impl AnimationNotifies {
+    pub fn tags(
+        &self,
+        state: CharacterState,
+        frame: usize,
+    ) -> impl Iterator<Item = AnimationTag> + '_ {
+        self.get(&state)
+            .into_iter()
+            .flatten()
+            .filter(move |(notify_frame, _)| *notify_frame == frame)
+            .map(|(_, tag)| *tag)
+    }
+}
+
+/// A notify frame of a character's animation was entered.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub state: CharacterState,
+    pub tag: AnimationTag,
+}
+
+/// A character entered a state: its animation (re)starts.
+///
+/// Looping animations start again each loop.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct AnimationStarted {
+    pub entity: Entity,
+    pub state: CharacterState,
+}
+
+/// The last frame of a character's animation is over.
+///
+/// Not sent when the animation is interrupted by another state.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub state: CharacterState,
+}
+
+fn send_notifies(
+    animation_events: &mut EventWriter<AnimationEvent>,
+    notifies: Option<&AnimationNotifies>,
+    entity: Entity,
+    state: CharacterState,
+    frame: usize,
+) {
+    let Some(notifies) = notifies else {
+        return;
+    };
+    for tag in notifies.tags(state, frame) {
+        animation_events.send(AnimationEvent { entity, state, tag });
+    }
+}
+
 pub fn animate_sprite_sheet(
     mut commands: Commands,
-    time: Res<Time>,
-    mut query: Query<(Entity, &mut SpriteSheetAnimation, &mut TextureAtlasSprite)>,
+    game_time: Res<GameTime>,
+    mut query: Query<(
+        Entity,
+        &mut SpriteSheetAnimation,
+        &mut TextureAtlasSprite,
+        Option<&Frozen>,
+    )>,
 ) {
-    for (entity, mut animation, mut sprite) in query.iter_mut() {
-        animation.timer.tick(time.delta());
+    for (entity, mut animation, mut sprite, frozen) in query.iter_mut() {
+        animation.timer.tick(game_time.delta_for(frozen));
 
         if animation.timer.finished() {
             if sprite.index >= animation.end_index {
@@ -71,32 +167,60 @@ pub fn animate_sprite_sheet(
 /// Jump directly to the correct frame when the state has changed.
 pub fn jump_frame_character_state(
     mut commands: Commands,
+    mut started_events: EventWriter<AnimationStarted>,
+    mut animation_events: EventWriter<AnimationEvent>,
     mut query: Query<
         (
             Entity,
             &AnimationIndices,
+            Option<&AnimationNotifies>,
             &mut TextureAtlasSprite,
             &CharacterState,
         ),
         Changed<CharacterState>,
     >,
 ) {
-    for (character, indices, mut sprite, character_state) in &mut query {
+    for (character, indices, notifies, mut sprite, character_state) in &mut query {
         // info!("{character_state:#?}",);
         let (first_indice, _, _) = &indices.get(character_state).unwrap();
         sprite.index = *first_indice;
+        started_events.send(AnimationStarted {
+            entity: character,
+            state: *character_state,
+        });
+        send_notifies(
+            &mut animation_events,
+            notifies,
+            character,
+            *character_state,
+            0,
+        );
 
-        match character_state {
-            // when running each time the anim loops it triggers this match arm
-            CharacterState::Idle => {
-                commands.entity(character).insert(TempoAnimation(Timer::new(
-                    Duration::from_secs_f32(rand::thread_rng().gen_range(0.1..=5.)),
-                    TimerMode::Once,
-                )));
-            }
-            _ => {
-                commands.entity(character).remove::<TempoAnimation>();
-            }
+        if *character_state != CharacterState::Idle {
+            commands.entity(character).remove::<TempoAnimation>();
+        }
+    }
+}
+
+/// Wait a random time before (re)starting the idle animation.
+pub fn randomize_idle_tempo(
+    mut commands: Commands,
+    juice_settings: Res<JuiceSettings>,
+    mut rng: ResMut<GameRng>,
+    query: Query<(Entity, &CharacterState), Changed<CharacterState>>,
+) {
+    let max_tempo = 5. * juice_settings.idle_tempo.intensity;
+    if max_tempo < 0.1 {
+        return;
+    }
+
+    for (character, character_state) in &query {
+        // when running each time the anim loops it triggers this
+        if *character_state == CharacterState::Idle {
+            commands.entity(character).insert(TempoAnimation(Timer::new(
+                Duration::from_secs_f32(rng.gen_range(0.1..=max_tempo)),
+                TimerMode::Once,
+            )));
         }
     }
 }
@@ -104,12 +228,12 @@ pub fn jump_frame_character_state(
 /// Could be a character or a menu entity.
 /// Decrease each frame the `TempoAnimation` timer.
 pub fn tempo_animation_timer(
-    time: Res<Time>,
+    game_time: Res<GameTime>,
     mut commands: Commands,
-    mut temporized_query: Query<(Entity, &mut TempoAnimation)>,
+    mut temporized_query: Query<(Entity, &mut TempoAnimation, Option<&Frozen>)>,
 ) {
-    for (entity, mut timer) in &mut temporized_query {
-        timer.tick(time.delta());
+    for (entity, mut timer, frozen) in &mut temporized_query {
+        timer.tick(game_time.delta_for(frozen));
         if timer.just_finished() {
             commands.entity(entity).remove::<TempoAnimation>();
         }
@@ -117,35 +241,42 @@ pub fn tempo_animation_timer(
 }
 
 pub fn animate_character(
-    time: Res<Time>,
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    mut finished_events: EventWriter<AnimationFinished>,
+    mut animation_events: EventWriter<AnimationEvent>,
     texture_atlases: Res<Assets<TextureAtlas>>,
     mut characters_query: Query<
         (
             Entity,
             &AnimationIndices,
+            Option<&AnimationNotifies>,
             &mut AnimationTimer,
             &mut TextureAtlasSprite,
             &Handle<TextureAtlas>,
             &mut CharacterState,
             &Name,
+            Option<&Frozen>,
         ),
-        (With<Player>, Without<TempoAnimation>),
+        Without<TempoAnimation>,
     >,
 ) {
     for (
-        _character,
+        character,
         indices,
+        notifies,
         mut timer,
         mut sprite,
         texture_atlas_handle,
         mut character_state,
         name,
+        frozen,
     ) in &mut characters_query
     {
-        timer.tick(time.delta());
+        timer.tick(game_time.delta_for(frozen));
 
         if timer.just_finished() {
-            let (_first_frame, last_frame, next_phase) = &indices.get(&character_state).unwrap();
+            let (first_frame, last_frame, next_phase) = &indices.get(&character_state).unwrap();
             // info!(
             //     "({_first_frame}, {last_frame}, {next_phase:#?}): {}",
             //     sprite.index
@@ -155,11 +286,26 @@ pub fn animate_character(
             let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
 
             if sprite.index == *last_frame {
+                finished_events.send(AnimationFinished {
+                    entity: character,
+                    state: *character_state,
+                });
+                if character_state.is_terminal() {
+                    commands.entity(character).remove::<AnimationTimer>();
+                    continue;
+                }
                 // update state
                 sprite.index = indices.get(next_phase).unwrap().0;
                 *character_state = *next_phase;
             } else if sprite.index + 1 < texture_atlas.textures.len() {
                 sprite.index += 1;
+                send_notifies(
+                    &mut animation_events,
+                    notifies,
+                    character,
+                    *character_state,
+                    sprite.index.saturating_sub(*first_frame),
+                );
             } else {
                 error!("anim limit reached: {}", name);
                 // commands.entity(character).remove::<AnimationTimer>();
@@ -0,0 +1,645 @@
+//! Tweens
+//!
+//! An [`Animator`] plays a [`Tweenable`] on its entity: eased [`Tween`]s of the transform,
+//! the sprite or text colour, or any field of a reflected component,
+//! arranged in sequences, parallel groups and repeats (looping or ping-pong).
+//! A [`TweenCompleted`] is sent when it ends.
+//!
+//! The tweenable is sampled from the elapsed time, never stepped:
+//! a tween of a sequence lands on its end value even if a frame skips past it.
+//!
+//! ie. a damage number is a translation, a scale bounce and a delayed fade in parallel,
+//! a muzzle flash a shrink and a fade, a faded out decal a sprite colour tween.
+
+use bevy::{
+    prelude::*,
+    reflect::{GetPath, Reflect},
+    transform::TransformSystem,
+};
+
+use crate::{
+    animations::easing::Easing,
+    hit_stop::{Frozen, GameTime},
+};
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TweenCompleted>().add_systems(
+            PostUpdate,
+            (animate_tweens, apply_field_tweens, complete_tweens)
+                .chain()
+                .in_set(TweenSet)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Systems starting tweens this frame run before this set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct TweenSet;
+
+/// A value a [`Tween`] can drive, from `start` to `end`.
+#[derive(Debug, Clone)]
+pub enum TweenTarget {
+    Translation {
+        start: Vec3,
+        end: Vec3,
+    },
+    /// Around the Z axis, in radian: it can spin more than a turn.
+    Rotation {
+        start: f32,
+        end: f32,
+    },
+    Scale {
+        start: Vec3,
+        end: Vec3,
+    },
+    /// Of a `Sprite` or a `TextureAtlasSprite`.
+    SpriteColor {
+        start: Color,
+        end: Color,
+    },
+    /// Of every section of a `Text`.
+    TextColor {
+        start: Color,
+        end: Color,
+    },
+    /// Any field of a reflected (and registered) component,
+    /// ie. `component: "CameraShake"`, `path: "trauma"`.
+    Field {
+        component: String,
+        path: String,
+        start: TweenValue,
+        end: TweenValue,
+    },
+}
+
+/// A reflected field's value.
+#[derive(Debug, Clone, Copy)]
+pub enum TweenValue {
+    F32(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Color(Color),
+}
+
+impl TweenValue {
+    /// `None` if `start` and `end` don't have the same type.
+    fn lerp(start: TweenValue, end: TweenValue, progress: f32) -> Option<TweenValue> {
+        match (start, end) {
+            (TweenValue::F32(start), TweenValue::F32(end)) => {
+                Some(TweenValue::F32(start + (end - start) * progress))
+            }
+            (TweenValue::Vec2(start), TweenValue::Vec2(end)) => {
+                Some(TweenValue::Vec2(start.lerp(end, progress)))
+            }
+            (TweenValue::Vec3(start), TweenValue::Vec3(end)) => {
+                Some(TweenValue::Vec3(start.lerp(end, progress)))
+            }
+            (TweenValue::Color(start), TweenValue::Color(end)) => {
+                Some(TweenValue::Color(lerp_color(start, end, progress)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `false` if the field doesn't have this type.
+    fn write(self, field: &mut dyn Reflect) -> bool {
+        match self {
+            TweenValue::F32(value) => field.downcast_mut::<f32>().map(|field| *field = value),
+            TweenValue::Vec2(value) => field.downcast_mut::<Vec2>().map(|field| *field = value),
+            TweenValue::Vec3(value) => field.downcast_mut::<Vec3>().map(|field| *field = value),
+            TweenValue::Color(value) => field.downcast_mut::<Color>().map(|field| *field = value),
+        }
+        .is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Tween {
+    pub target: TweenTarget,
+    /// in seconds
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl Tween {
+    pub fn new(target: TweenTarget, duration: f32) -> Self {
+        Tween {
+            target,
+            duration,
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// Total number of plays.
+    Times(u32),
+    Forever,
+}
+
+#[derive(Debug, Clone)]
+pub enum Tweenable {
+    Tween(Tween),
+    /// in seconds
+    Delay(f32),
+    /// One after the other.
+    Sequence(Vec<Tweenable>),
+    /// All at once, lasting as long as the longest.
+    Parallel(Vec<Tweenable>),
+    Repeat {
+        tweenable: Box<Tweenable>,
+        repeat: Repeat,
+        /// Every other play goes backward.
+        ping_pong: bool,
+    },
+}
+
+impl From<Tween> for Tweenable {
+    fn from(tween: Tween) -> Self {
+        Tweenable::Tween(tween)
+    }
+}
+
+impl Tweenable {
+    /// Play `next` after this one.
+    pub fn then(self, next: impl Into<Tweenable>) -> Tweenable {
+        match self {
+            Tweenable::Sequence(mut tweenables) => {
+                tweenables.push(next.into());
+                Tweenable::Sequence(tweenables)
+            }
+            tweenable => Tweenable::Sequence(vec![tweenable, next.into()]),
+        }
+    }
+
+    /// Play `other` at the same time.
+    pub fn with(self, other: impl Into<Tweenable>) -> Tweenable {
+        match self {
+            Tweenable::Parallel(mut tweenables) => {
+                tweenables.push(other.into());
+                Tweenable::Parallel(tweenables)
+            }
+            tweenable => Tweenable::Parallel(vec![tweenable, other.into()]),
+        }
+    }
+
+    pub fn repeat(self, repeat: Repeat) -> Tweenable {
+        Tweenable::Repeat {
+            tweenable: Box::new(self),
+            repeat,
+            ping_pong: false,
+        }
+    }
+
+    /// Repeat, going backward every other play.
+    pub fn ping_pong(self, repeat: Repeat) -> Tweenable {
+        Tweenable::Repeat {
+            tweenable: Box::new(self),
+            repeat,
+            ping_pong: true,
+        }
+    }
+
+    /// in seconds, infinite when repeated forever
+    pub fn duration(&self) -> f32 {
+        match self {
+            Tweenable::Tween(tween) => tween.duration.max(0.),
+            Tweenable::Delay(delay) => delay.max(0.),
+            Tweenable::Sequence(tweenables) => tweenables.iter().map(Tweenable::duration).sum(),
+            Tweenable::Parallel(tweenables) => tweenables
+                .iter()
+                .map(Tweenable::duration)
+                .fold(0., f32::max),
+            Tweenable::Repeat {
+                tweenable, repeat, ..
+            } => match repeat {
+                Repeat::Times(times) => tweenable.duration() * *times as f32,
+                Repeat::Forever => f32::INFINITY,
+            },
+        }
+    }
+
+    /// Call `apply` with each started tween and its eased progress at `elapsed`.
+    pub fn sample(&self, elapsed: f32, apply: &mut dyn FnMut(&TweenTarget, f32)) {
+        match self {
+            Tweenable::Tween(tween) => {
+                let t = if tween.duration > 0. {
+                    elapsed / tween.duration
+                } else {
+                    1.
+                };
+                apply(&tween.target, tween.easing.ease(t));
+            }
+            Tweenable::Delay(_) => {}
+            Tweenable::Sequence(tweenables) => {
+                let mut start = 0.;
+                for tweenable in tweenables {
+                    // the next ones haven't started: don't set their start values yet
+                    if elapsed < start {
+                        break;
+                    }
+                    let duration = tweenable.duration();
+                    tweenable.sample((elapsed - start).min(duration), apply);
+                    start += duration;
+                }
+            }
+            Tweenable::Parallel(tweenables) => {
+                for tweenable in tweenables {
+                    tweenable.sample(elapsed.min(tweenable.duration()), apply);
+                }
+            }
+            Tweenable::Repeat {
+                tweenable,
+                repeat,
+                ping_pong,
+            } => {
+                // never played
+                if *repeat == Repeat::Times(0) {
+                    return;
+                }
+                let duration = tweenable.duration();
+                if duration <= 0. || !duration.is_finite() {
+                    tweenable.sample(elapsed, apply);
+                    return;
+                }
+
+                let (play, local) = match repeat {
+                    Repeat::Times(times) if elapsed >= duration * *times as f32 => {
+                        (times.saturating_sub(1), duration)
+                    }
+                    _ => {
+                        let play = (elapsed / duration).floor();
+                        (play as u32, elapsed - play * duration)
+                    }
+                };
+                let local = if *ping_pong && play % 2 == 1 {
+                    duration - local
+                } else {
+                    local
+                };
+                tweenable.sample(local, apply);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OnCompleted {
+    /// Leave the [`Animator`], on its end values.
+    #[default]
+    Keep,
+    Remove,
+    Despawn,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum AnimatorState {
+    #[default]
+    Playing,
+    /// This frame.
+    Completed,
+    Done,
+}
+
+/// Plays a [`Tweenable`] on its entity.
+#[derive(Debug, Clone, Component)]
+pub struct Animator {
+    pub tweenable: Tweenable,
+    /// in seconds
+    pub elapsed: f32,
+    /// `2.` plays twice as fast.
+    pub speed: f32,
+    /// Sent back in the [`TweenCompleted`].
+    pub tag: u64,
+    pub on_completed: OnCompleted,
+    state: AnimatorState,
+}
+
+impl Animator {
+    pub fn new(tweenable: impl Into<Tweenable>) -> Self {
+        Animator {
+            tweenable: tweenable.into(),
+            elapsed: 0.,
+            speed: 1.,
+            tag: 0,
+            on_completed: OnCompleted::default(),
+            state: AnimatorState::default(),
+        }
+    }
+
+    pub fn with_tag(mut self, tag: u64) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    pub fn on_completed(mut self, on_completed: OnCompleted) -> Self {
+        self.on_completed = on_completed;
+        self
+    }
+}
+
+/// An [`Animator`] reached the end of its tweenable.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct TweenCompleted {
+    pub entity: Entity,
+    pub tag: u64,
+}
+
+fn animate_tweens(
+    game_time: Res<GameTime>,
+    mut animator_query: Query<(
+        &mut Animator,
+        Option<&mut Transform>,
+        Option<&mut Sprite>,
+        Option<&mut TextureAtlasSprite>,
+        Option<&mut Text>,
+        Option<&Frozen>,
+    )>,
+) {
+    for (mut animator, mut transform, mut sprite, mut atlas_sprite, mut text, frozen) in
+        &mut animator_query
+    {
+        if animator.state != AnimatorState::Playing {
+            continue;
+        }
+
+        let delta = game_time.delta_for(frozen).as_secs_f32() * animator.speed;
+        let duration = animator.tweenable.duration();
+        animator.elapsed = (animator.elapsed + delta).min(duration);
+
+        animator
+            .tweenable
+            .sample(animator.elapsed, &mut |target, progress| match target {
+                TweenTarget::Translation { start, end } => {
+                    if let Some(transform) = transform.as_mut() {
+                        transform.translation = start.lerp(*end, progress);
+                    }
+                }
+                TweenTarget::Rotation { start, end } => {
+                    if let Some(transform) = transform.as_mut() {
+                        transform.rotation =
+                            Quat::from_rotation_z(start + (end - start) * progress);
+                    }
+                }
+                TweenTarget::Scale { start, end } => {
+                    if let Some(transform) = transform.as_mut() {
+                        transform.scale = start.lerp(*end, progress);
+                    }
+                }
+                TweenTarget::SpriteColor { start, end } => {
+                    let color = lerp_color(*start, *end, progress);
+                    if let Some(sprite) = sprite.as_mut() {
+                        sprite.color = color;
+                    }
+                    if let Some(atlas_sprite) = atlas_sprite.as_mut() {
+                        atlas_sprite.color = color;
+                    }
+                }
+                TweenTarget::TextColor { start, end } => {
+                    if let Some(text) = text.as_mut() {
+                        let color = lerp_color(*start, *end, progress);
+                        for section in &mut text.sections {
+                            section.style.color = color;
+                        }
+                    }
+                }
+                // needs the whole world, see `apply_field_tweens`
+                TweenTarget::Field { .. } => {}
+            });
+
+        if animator.elapsed >= duration {
+            animator.state = AnimatorState::Completed;
+        }
+    }
+}
+
+/// Write the [`TweenTarget::Field`]s through reflection.
+fn apply_field_tweens(world: &mut World) {
+    let mut writes = Vec::new();
+    let mut animator_query = world.query::<(Entity, &Animator)>();
+    for (entity, animator) in animator_query.iter(world) {
+        if animator.state == AnimatorState::Done {
+            continue;
+        }
+        animator
+            .tweenable
+            .sample(animator.elapsed, &mut |target, progress| {
+                if let TweenTarget::Field {
+                    component,
+                    path,
+                    start,
+                    end,
+                } = target
+                {
+                    if let Some(value) = TweenValue::lerp(*start, *end, progress) {
+                        writes.push((entity, component.clone(), path.clone(), value));
+                    }
+                }
+            });
+    }
+    if writes.is_empty() {
+        return;
+    }
+
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    for (entity, component, path, value) in writes {
+        let Some(reflect_component) = type_registry
+            .get_with_short_name(&component)
+            .or_else(|| type_registry.get_with_name(&component))
+            .and_then(|registration| registration.data::<ReflectComponent>())
+        else {
+            warn!("can't tween {component}: not a registered component");
+            continue;
+        };
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        let Some(mut reflected) = reflect_component.reflect_mut(&mut entity_mut) else {
+            continue;
+        };
+        let written = reflected
+            .reflect_path_mut(path.as_str())
+            .map_or(false, |field| value.write(field));
+        if !written {
+            warn!("can't tween {component}.{path} with {value:?}");
+        }
+    }
+}
+
+fn complete_tweens(
+    mut commands: Commands,
+    mut completed_events: EventWriter<TweenCompleted>,
+    mut animator_query: Query<(Entity, &mut Animator)>,
+) {
+    for (entity, mut animator) in &mut animator_query {
+        if animator.state != AnimatorState::Completed {
+            continue;
+        }
+        animator.state = AnimatorState::Done;
+
+        completed_events.send(TweenCompleted {
+            entity,
+            tag: animator.tag,
+        });
+        match animator.on_completed {
+            OnCompleted::Keep => {}
+            OnCompleted::Remove => {
+                commands.entity(entity).remove::<Animator>();
+            }
+            OnCompleted::Despawn => {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Blend every channel, alpha included.
+pub fn lerp_color(start: Color, end: Color, progress: f32) -> Color {
+    let start = Vec4::from(start.as_rgba_f32());
+    let end = Vec4::from(end.as_rgba_f32());
+    let color = start.lerp(end, progress);
+    Color::rgba(color.x, color.y, color.z, color.w)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// A tween of a plain number, from `start` to `end` in `duration` seconds.
+    fn number(start: f32, end: f32, duration: f32) -> Tween {
+        Tween::new(TweenTarget::Rotation { start, end }, duration)
+    }
+
+    /// The value of each sampled tween at `elapsed`.
+    fn sample(tweenable: &Tweenable, elapsed: f32) -> Vec<f32> {
+        let mut values = Vec::new();
+        tweenable.sample(elapsed, &mut |target, progress| {
+            if let TweenTarget::Rotation { start, end } = target {
+                values.push(start + (end - start) * progress);
+            }
+        });
+        values
+    }
+
+    #[test]
+    fn sequence_plays_one_after_the_other() {
+        let sequence = Tweenable::from(number(0., 1., 1.)).then(number(10., 11., 1.));
+
+        assert_eq!(sequence.duration(), 2.);
+        assert_eq!(sample(&sequence, 0.5), [0.5]);
+        assert_eq!(sample(&sequence, 1.5), [1., 10.5]);
+        // a skipped frame still lands on the end values
+        assert_eq!(sample(&sequence, 5.), [1., 11.]);
+    }
+
+    #[test]
+    fn parallel_lasts_as_long_as_the_longest() {
+        let parallel = Tweenable::from(number(0., 1., 1.)).with(number(0., 2., 2.));
+
+        assert_eq!(parallel.duration(), 2.);
+        assert_eq!(sample(&parallel, 0.5), [0.5, 0.5]);
+        assert_eq!(sample(&parallel, 1.5), [1., 1.5]);
+    }
+
+    #[test]
+    fn repeat_restarts_each_play() {
+        let repeat = Tweenable::from(number(0., 1., 1.)).repeat(Repeat::Times(3));
+
+        assert_eq!(repeat.duration(), 3.);
+        assert_eq!(sample(&repeat, 0.25), [0.25]);
+        assert_eq!(sample(&repeat, 2.25), [0.25]);
+        assert_eq!(sample(&repeat, 3.), [1.]);
+    }
+
+    #[test]
+    fn ping_pong_goes_backward_every_other_play() {
+        let ping_pong = Tweenable::from(number(0., 1., 1.)).ping_pong(Repeat::Times(2));
+
+        assert_eq!(sample(&ping_pong, 0.25), [0.25]);
+        assert_eq!(sample(&ping_pong, 1.25), [0.75]);
+        assert_eq!(sample(&ping_pong, 2.), [0.]);
+    }
+
+    #[test]
+    fn zero_repeat_is_never_sampled() {
+        let repeat = Tweenable::from(number(0., 1., 1.)).repeat(Repeat::Times(0));
+
+        assert_eq!(repeat.duration(), 0.);
+        assert!(sample(&repeat, 0.).is_empty());
+    }
+
+    #[test]
+    fn completed_once_at_the_end() {
+        let mut app = App::new();
+        app.init_resource::<GameTime>().add_plugins(TweenPlugin);
+        let entity = app
+            .world
+            .spawn((
+                Transform::default(),
+                Animator::new(Tween::new(
+                    TweenTarget::Translation {
+                        start: Vec3::ZERO,
+                        end: Vec3::X,
+                    },
+                    1.,
+                ))
+                .with_tag(7),
+            ))
+            .id();
+
+        let mut completed_reader = app.world.resource::<Events<TweenCompleted>>().get_reader();
+        let mut completed_at = Vec::new();
+        for frame in 1..=6 {
+            app.world
+                .resource_mut::<GameTime>()
+                .advance(Duration::from_millis(250));
+            app.update();
+            let events = app.world.resource::<Events<TweenCompleted>>();
+            for completed in completed_reader.iter(events) {
+                assert_eq!((completed.entity, completed.tag), (entity, 7));
+                completed_at.push(frame);
+            }
+        }
+
+        assert_eq!(completed_at, [4]);
+        assert_eq!(
+            app.world.get::<Transform>(entity).unwrap().translation,
+            Vec3::X
+        );
+    }
+
+    #[test]
+    fn despawned_when_completed() {
+        let mut app = App::new();
+        app.init_resource::<GameTime>().add_plugins(TweenPlugin);
+        let entity = app
+            .world
+            .spawn(Animator::new(Tweenable::Delay(0.5)).on_completed(OnCompleted::Despawn))
+            .id();
+
+        app.world
+            .resource_mut::<GameTime>()
+            .advance(Duration::from_millis(250));
+        app.update();
+        assert!(app.world.get_entity(entity).is_some());
+
+        app.world
+            .resource_mut::<GameTime>()
+            .advance(Duration::from_millis(250));
+        app.update();
+        assert!(app.world.get_entity(entity).is_none());
+    }
+}
@@ -0,0 +1,194 @@
+//! Camera Juice
+//!
+//! Trauma-based screen shake for the [`PlayerCamera`].
+//!
+//! Any system can send an [`AddTrauma`] event when something impactful happens.
+//! The trauma decays over time and the shake (offset and rotation) is driven by noise
+//! scaled by `trauma²`, so small hits barely move the camera while big ones really kick.
+//!
+//! A [`Knockback`] sent to the camera kicks it by the distance the impulse would push a body,
+//! then the kick springs back.
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{
+    combat::knockback::Knockback,
+    constants::camera::{
+        CAMERA_KICK_DECAY, CAMERA_SHAKE_DECAY, CAMERA_SHAKE_MAX_ANGLE, CAMERA_SHAKE_MAX_OFFSET,
+        CAMERA_SHAKE_NOISE_FREQUENCY,
+    },
+    juice::{juice_enabled, JuiceEffect, JuiceSettings},
+    PlayerCamera,
+};
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AddTrauma>()
+            // The shake is removed before any `Update` system (ie. `camera_follow`)
+            // reads the camera's transform, and is reapplied just before propagation.
+            // This way the follow lerp never chases its own shake.
+            .add_systems(PreUpdate, remove_camera_shake)
+            .add_systems(
+                PostUpdate,
+                (add_trauma, kick_camera, apply_camera_shake)
+                    .chain()
+                    .run_if(juice_enabled(JuiceEffect::Shake))
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Add some trauma (`[0; 1]`) to every [`CameraShake`].
+///
+/// The total trauma is clamped to `1.`.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct AddTrauma(pub f32);
+
+#[derive(Debug, Reflect, Component)]
+pub struct CameraShake {
+    /// Current trauma, in `[0; 1]`.
+    pub trauma: f32,
+    /// Offset (in world unit) reached at full trauma.
+    pub max_offset: f32,
+    /// Rotation (in radian) reached at full trauma.
+    pub max_angle: f32,
+    /// Trauma lost per second.
+    pub decay: f32,
+    /// How fast the noise evolves: higher means a more jittery shake.
+    pub noise_frequency: f32,
+    /// Current kick offset (in world unit).
+    pub kick: Vec2,
+    /// Exponential decay of the kick, per second.
+    pub kick_decay: f32,
+    /// Offset applied the last frame, to be removed the next one.
+    applied_offset: Vec2,
+    /// Rotation applied the last frame, to be removed the next one.
+    applied_angle: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        CameraShake {
+            trauma: 0.,
+            max_offset: CAMERA_SHAKE_MAX_OFFSET,
+            max_angle: CAMERA_SHAKE_MAX_ANGLE,
+            decay: CAMERA_SHAKE_DECAY,
+            noise_frequency: CAMERA_SHAKE_NOISE_FREQUENCY,
+            kick: Vec2::ZERO,
+            kick_decay: CAMERA_KICK_DECAY,
+            applied_offset: Vec2::ZERO,
+            applied_angle: 0.,
+        }
+    }
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0., 1.);
+    }
+
+    /// The actual shake intensity.
+    pub fn shake(&self) -> f32 {
+        self.trauma * self.trauma
+    }
+}
+
+fn add_trauma(
+    juice_settings: Res<JuiceSettings>,
+    mut add_trauma_events: EventReader<AddTrauma>,
+    mut camera_query: Query<&mut CameraShake>,
+) {
+    let intensity = juice_settings.intensity(JuiceEffect::Shake);
+    for AddTrauma(trauma) in add_trauma_events.iter() {
+        for mut camera_shake in &mut camera_query {
+            camera_shake.add_trauma(*trauma * intensity);
+        }
+    }
+}
+
+fn kick_camera(
+    juice_settings: Res<JuiceSettings>,
+    mut knockback_events: EventReader<Knockback>,
+    mut camera_query: Query<&mut CameraShake>,
+) {
+    let intensity = juice_settings.intensity(JuiceEffect::Shake);
+    for knockback in knockback_events.iter() {
+        if let Ok(mut camera_shake) = camera_query.get_mut(knockback.target) {
+            camera_shake.kick += knockback.distance() * intensity;
+        }
+    }
+}
+
+/// Restore the camera to its unshaken transform.
+fn remove_camera_shake(mut camera_query: Query<(&mut Transform, &mut CameraShake)>) {
+    for (mut transform, mut camera_shake) in &mut camera_query {
+        transform.translation -= camera_shake.applied_offset.extend(0.);
+        transform.rotate_z(-camera_shake.applied_angle);
+
+        camera_shake.applied_offset = Vec2::ZERO;
+        camera_shake.applied_angle = 0.;
+    }
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    mut camera_query: Query<(&mut Transform, &mut CameraShake), With<PlayerCamera>>,
+) {
+    for (mut transform, mut camera_shake) in &mut camera_query {
+        let decay = camera_shake.decay * time.delta_seconds();
+        camera_shake.add_trauma(-decay);
+        let kick_decay = (-camera_shake.kick_decay * time.delta_seconds()).exp();
+        camera_shake.kick *= kick_decay;
+        if camera_shake.kick.length_squared() < 1e-4 {
+            camera_shake.kick = Vec2::ZERO;
+        }
+
+        let shake = camera_shake.shake();
+        if shake == 0. && camera_shake.kick == Vec2::ZERO {
+            continue;
+        }
+
+        let t = time.elapsed_seconds() * camera_shake.noise_frequency;
+        let offset = Vec2::new(noise(0, t), noise(1, t)) * camera_shake.max_offset * shake
+            + camera_shake.kick;
+        let angle = noise(2, t) * camera_shake.max_angle * shake;
+
+        transform.translation += offset.extend(0.);
+        transform.rotate_z(angle);
+
+        camera_shake.applied_offset = offset;
+        camera_shake.applied_angle = angle;
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                    Noise                                   */
+/* -------------------------------------------------------------------------- */
+
+/// Smooth 1D value noise in `[-1; 1]`.
+///
+/// Each `seed` gives an independent channel (x, y, angle...).
+fn noise(seed: u32, t: f32) -> f32 {
+    let floor = t.floor();
+    let fract = t - floor;
+    // smoothstep between the two lattice values
+    let smooth = fract * fract * (3. - 2. * fract);
+
+    let a = lattice(seed, floor as i32);
+    let b = lattice(seed, floor as i32 + 1);
+    a + (b - a) * smooth
+}
+
+/// Pseudo-random value in `[-1; 1]` for an integer coordinate.
+fn lattice(seed: u32, x: i32) -> f32 {
+    let mut hash = (x as u32)
+        .wrapping_mul(0x27d4_eb2d)
+        .wrapping_add(seed.wrapping_mul(0x1656_67b1));
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+
+    (hash as f32 / u32::MAX as f32) * 2. - 1.
+}
@@ -0,0 +1,223 @@
+//! Dash
+//!
+//! A short burst along the input direction (or where the character faces),
+//! with optional i-frames and a trail of fading afterimages.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    animations::sprite_sheet_animation::CharacterState,
+    combat::health::{Dead, Invulnerable},
+    constants::character::player::{
+        AFTERIMAGE_ALPHA, AFTERIMAGE_INTERVAL, AFTERIMAGE_LIFETIME, PLAYER_DASH_COOLDOWN,
+        PLAYER_DASH_DISTANCE, PLAYER_DASH_DURATION,
+    },
+    controls::{
+        actions::{Action, ActionState},
+        buffer::InputBuffer,
+    },
+    hit_stop::{Frozen, GameTime},
+    movement::Speed,
+    playing,
+};
+
+use super::player::{player_movement, Player};
+
+pub struct DashPlugin;
+
+impl Plugin for DashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (tick_dash_cooldown, start_dash, dash, fade_afterimages)
+                .chain()
+                .before(player_movement)
+                .run_if(playing),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Component)]
+#[reflect(Component)]
+pub struct Dash {
+    pub distance: f32,
+    /// in seconds
+    pub duration: f32,
+    pub cooldown: Timer,
+    /// Grant [`Invulnerable`] during the dash.
+    pub invulnerable: bool,
+    /// in seconds, `0.` to disable the trail
+    pub afterimage_interval: f32,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(PLAYER_DASH_COOLDOWN, TimerMode::Once);
+        // ready at spawn
+        cooldown.tick(cooldown.duration());
+
+        Dash {
+            distance: PLAYER_DASH_DISTANCE,
+            duration: PLAYER_DASH_DURATION,
+            cooldown,
+            invulnerable: true,
+            afterimage_interval: AFTERIMAGE_INTERVAL,
+        }
+    }
+}
+
+/// The character is currently dashing.
+#[derive(Debug, Component)]
+pub struct Dashing {
+    velocity: Vec2,
+    timer: Timer,
+    afterimage_timer: Timer,
+}
+
+/// A fading copy of a dashing character.
+#[derive(Debug, Component)]
+pub struct Afterimage(Timer);
+
+fn tick_dash_cooldown(
+    game_time: Res<GameTime>,
+    mut dash_query: Query<(&mut Dash, Option<&Frozen>)>,
+) {
+    for (mut dash, frozen) in &mut dash_query {
+        dash.cooldown.tick(game_time.delta_for(frozen));
+    }
+}
+
+fn start_dash(
+    mut commands: Commands,
+    action_state: Res<ActionState>,
+    mut input_buffer: ResMut<InputBuffer>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Dash,
+            &mut Velocity,
+            &TextureAtlasSprite,
+            &mut CharacterState,
+        ),
+        (With<Player>, Without<Dashing>, Without<Dead>),
+    >,
+) {
+    let Ok((player, mut dash, mut rb_vel, sprite, mut player_state)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    if !dash.cooldown.finished() || dash.duration <= 0. {
+        return;
+    }
+    if !input_buffer.consume_buffered(Action::Dash) {
+        return;
+    }
+
+    // without input, dash forward
+    let direction = action_state
+        .movement()
+        .try_normalize()
+        .unwrap_or(if sprite.flip_x { Vec2::NEG_X } else { Vec2::X });
+    let velocity = direction * dash.distance / dash.duration;
+
+    rb_vel.linvel = velocity;
+    *player_state = CharacterState::Dash;
+    dash.cooldown.reset();
+
+    let mut afterimage_timer = Timer::from_seconds(dash.afterimage_interval, TimerMode::Repeating);
+    // the first afterimage is left at the start
+    afterimage_timer.tick(afterimage_timer.duration());
+
+    commands.entity(player).insert(Dashing {
+        velocity,
+        timer: Timer::from_seconds(dash.duration, TimerMode::Once),
+        afterimage_timer,
+    });
+    if dash.invulnerable {
+        commands.entity(player).insert(Invulnerable);
+    }
+}
+
+/// Keep the dash velocity, leave afterimages and give the control back at the end.
+fn dash(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    mut dashing_query: Query<(
+        Entity,
+        &mut Dashing,
+        &Dash,
+        &Speed,
+        &mut Velocity,
+        &Transform,
+        &TextureAtlasSprite,
+        &Handle<TextureAtlas>,
+        &mut CharacterState,
+        Option<&Frozen>,
+    )>,
+) {
+    for (
+        character,
+        mut dashing,
+        dash,
+        speed,
+        mut rb_vel,
+        transform,
+        sprite,
+        texture_atlas,
+        mut character_state,
+        frozen,
+    ) in &mut dashing_query
+    {
+        let delta = game_time.delta_for(frozen);
+        dashing.timer.tick(delta);
+        dashing.afterimage_timer.tick(delta);
+
+        if dash.afterimage_interval > 0. && dashing.afterimage_timer.just_finished() {
+            let mut afterimage_sprite = sprite.clone();
+            afterimage_sprite.color.set_a(AFTERIMAGE_ALPHA);
+
+            commands.spawn((
+                SpriteSheetBundle {
+                    sprite: afterimage_sprite,
+                    texture_atlas: texture_atlas.clone(),
+                    // behind the character
+                    transform: transform.with_translation(transform.translation - 0.01 * Vec3::Z),
+                    ..default()
+                },
+                Afterimage(Timer::from_seconds(AFTERIMAGE_LIFETIME, TimerMode::Once)),
+                Name::new("Afterimage"),
+            ));
+        }
+
+        if dashing.timer.finished() {
+            // exit at max speed, to run or skid to a stop
+            rb_vel.linvel = dashing.velocity.clamp_length_max(**speed);
+            *character_state = CharacterState::Idle;
+            commands
+                .entity(character)
+                .remove::<(Dashing, Invulnerable)>();
+        } else {
+            rb_vel.linvel = dashing.velocity;
+        }
+    }
+}
+
+fn fade_afterimages(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    mut afterimage_query: Query<(Entity, &mut Afterimage, &mut TextureAtlasSprite)>,
+) {
+    for (afterimage, mut timer, mut sprite) in &mut afterimage_query {
+        timer.0.tick(game_time.delta());
+        sprite
+            .color
+            .set_a(AFTERIMAGE_ALPHA * timer.0.percent_left());
+
+        if timer.0.finished() {
+            commands.entity(afterimage).despawn();
+        }
+    }
+}
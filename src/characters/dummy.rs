@@ -0,0 +1,121 @@
+//! Training Dummy
+//!
+//! A target to try weapons on: it gets pushed around, staggers and dies.
+//! Left alone for [`DUMMY_HEAL_DELAY`], it heals back to full health.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    animations::{
+        sprite_sheet_animation::{AnimationIndices, CharacterState},
+        CharacterSpriteSheet,
+    },
+    combat::health::{Damaged, Dead, Heal, Health},
+    constants::character::{
+        npcs::{
+            movement::NPC_SPEED, DUMMY_HEALTH, DUMMY_HEAL_DELAY, DUMMY_HURTBOX,
+            DUMMY_LINEAR_DAMPING, DUMMY_SPAWN, NPC_SCALE,
+        },
+        player::{PLAYER_DEATH_FRAMES, PLAYER_HIT_FRAMES, PLAYER_IDLE_FRAMES},
+        CHAR_SENSOR_Y_OFFSET,
+    },
+    effects::{flash::HitFlash, squash::SquashStretch},
+    hit_stop::GameTime,
+    movement::MovementBundle,
+    playing,
+};
+
+/// The dummy looks like the player, tinted.
+const DUMMY_COLOR: Color = Color::rgb(1., 0.7, 0.7);
+
+pub struct DummyPlugin;
+
+impl Plugin for DummyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_training_dummy)
+            .add_systems(Update, heal_training_dummy.run_if(playing));
+    }
+}
+
+#[derive(Component)]
+pub struct TrainingDummy {
+    /// Since it was last damaged.
+    heal_delay: Timer,
+}
+
+impl Default for TrainingDummy {
+    fn default() -> Self {
+        TrainingDummy {
+            heal_delay: Timer::from_seconds(DUMMY_HEAL_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+fn spawn_training_dummy(mut commands: Commands, characters_spritesheet: Res<CharacterSpriteSheet>) {
+    let mut sprite = TextureAtlasSprite::new(PLAYER_IDLE_FRAMES.0);
+    sprite.color = DUMMY_COLOR;
+    // facing the player
+    sprite.flip_x = true;
+
+    let mut animation_indices = AnimationIndices(HashMap::new());
+    animation_indices.insert(CharacterState::Idle, PLAYER_IDLE_FRAMES);
+    animation_indices.insert(CharacterState::Hit, PLAYER_HIT_FRAMES);
+    animation_indices.insert(CharacterState::Death, PLAYER_DEATH_FRAMES);
+
+    commands
+        .spawn((
+            SpriteSheetBundle {
+                sprite,
+                texture_atlas: characters_spritesheet.texture_atlas.clone(),
+                transform: Transform::from_xyz(DUMMY_SPAWN.0, DUMMY_SPAWN.1, DUMMY_SPAWN.2)
+                    .with_scale(Vec3::splat(NPC_SCALE)),
+                ..default()
+            },
+            Name::new("Training Dummy"),
+            TrainingDummy::default(),
+            MovementBundle::new(NPC_SPEED, CharacterState::Idle, animation_indices),
+            Health::new(DUMMY_HEALTH),
+            HitFlash::default(),
+            SquashStretch::default(),
+            RigidBody::Dynamic,
+            LockedAxes::ROTATION_LOCKED,
+            Damping {
+                linear_damping: DUMMY_LINEAR_DAMPING,
+                angular_damping: 0.,
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Collider::cuboid(DUMMY_HURTBOX.0, DUMMY_HURTBOX.1),
+                // not deformed by the squash & stretch
+                ColliderScale::Absolute(Vec2::splat(NPC_SCALE)),
+                TransformBundle::from_transform(Transform::from_xyz(0., CHAR_SENSOR_Y_OFFSET, 0.)),
+                Name::new("Hurtbox"),
+            ));
+        });
+}
+
+fn heal_training_dummy(
+    game_time: Res<GameTime>,
+    mut damaged_events: EventReader<Damaged>,
+    mut heal_events: EventWriter<Heal>,
+    mut dummy_query: Query<(Entity, &mut TrainingDummy, &Health), Without<Dead>>,
+) {
+    for damaged in damaged_events.iter() {
+        if let Ok((_, mut dummy, _)) = dummy_query.get_mut(damaged.target) {
+            dummy.heal_delay.reset();
+        }
+    }
+
+    for (entity, mut dummy, health) in &mut dummy_query {
+        if dummy.heal_delay.tick(game_time.delta()).just_finished() && health.current < health.max {
+            heal_events.send(Heal {
+                target: entity,
+                amount: health.max - health.current,
+            });
+        }
+    }
+}
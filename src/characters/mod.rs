@@ -1 +1,13 @@
+use bevy::prelude::*;
+
+pub mod dash;
+pub mod dummy;
 pub mod player;
+
+pub struct CharactersPlugin;
+
+impl Plugin for CharactersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((dash::DashPlugin, dummy::DummyPlugin, player::PlayerPlugin));
+    }
+}
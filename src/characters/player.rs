@@ -5,99 +5,121 @@ use bevy_rapier2d::prelude::*;
 
 use crate::{
     animations::{
-        sprite_sheet_animation::{AnimationIndices, CharacterState},
+        sprite_sheet_animation::{AnimationIndices, AnimationNotifies, CharacterState},
         CharacterSpriteSheet,
     },
+    combat::{
+        aim::Aim,
+        health::{Dead, Health},
+        melee::MeleeAttack,
+        shoot::Weapon,
+    },
     constants::{
         character::player::{
-            CAMERA_INTERPOLATION, PLAYER_IDLE_FRAMES, PLAYER_RUN_FRAMES, PLAYER_SCALE,
+            CAMERA_INTERPOLATION, PLAYER_DASH_FRAMES, PLAYER_DEATH_FRAMES, PLAYER_HEALTH,
+            PLAYER_HIT_FRAMES, PLAYER_IDLE_FRAMES, PLAYER_INVULNERABILITY, PLAYER_MELEE_FRAMES,
+            PLAYER_MELEE_NOTIFIES, PLAYER_RUN_FRAMES, PLAYER_RUN_NOTIFIES, PLAYER_SCALE,
+            PLAYER_SHOOT_FRAMES, PLAYER_SHOOT_NOTIFIES, PLAYER_SKID_SPEED_RATIO,
         },
+        effects::RED_FLASH,
+        layers::CHARACTERS_Z,
         TILE_SIZE,
     },
-    controls::KeyBindings,
-    movement::{MovementBundle, Speed},
-    playing, GameState, PlayerCamera,
+    controls::actions::ActionState,
+    effects::{flash::HitFlash, squash::SquashStretch},
+    hit_stop::{Frozen, GameTime},
+    juice::{juice_enabled, JuiceEffect, JuiceSettings},
+    movement::{MovementBundle, MovementFeel, Skidded, Speed},
+    playing, PlayerCamera,
 };
 
+use super::dash::{Dash, Dashing};
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), spawn_player)
-            .add_systems(Update, (player_movement.run_if(playing), camera_follow));
+        app.add_event::<Skidded>()
+            .add_systems(Startup, spawn_player)
+            .add_systems(
+                Update,
+                (
+                    player_movement.run_if(playing),
+                    camera_follow.run_if(juice_enabled(JuiceEffect::CameraLerp)),
+                    camera_snap.run_if(not(juice_enabled(JuiceEffect::CameraLerp))),
+                ),
+            );
     }
 }
 
 #[derive(Component)]
 pub struct Player;
 
-fn player_movement(
-    key_bindings: Res<KeyBindings>,
-    keyboard_input: Res<Input<KeyCode>>,
+pub fn player_movement(
+    action_state: Res<ActionState>,
+    game_time: Res<GameTime>,
+    mut skid_events: EventWriter<Skidded>,
     mut player_query: Query<
         (
             Entity,
             &Speed,
+            Option<&MovementFeel>,
+            Option<&Frozen>,
             &mut Velocity,
-            &mut TextureAtlasSprite,
             &mut CharacterState,
         ),
-        With<Player>,
+        (With<Player>, Without<Dashing>, Without<Dead>),
     >,
 ) {
-    if let Ok((_player, speed, mut rb_vel, mut texture_atlas_sprite, mut player_state)) =
+    if let Ok((player, speed, movement_feel, frozen, mut rb_vel, mut player_state)) =
         player_query.get_single_mut()
     {
-        let up = keyboard_input.any_pressed(key_bindings.up());
-        let down = keyboard_input.any_pressed(key_bindings.down());
-        let left = keyboard_input.any_pressed(key_bindings.left());
-        let right = keyboard_input.any_pressed(key_bindings.right());
-
-        let x_axis = -(left as i8) + right as i8;
-        let y_axis = -(down as i8) + up as i8;
-
-        let mut vel_x = x_axis as f32 * **speed;
-        let mut vel_y = y_axis as f32 * **speed;
-
-        if x_axis != 0 && y_axis != 0 {
-            vel_x *= (std::f32::consts::PI / 4.).cos();
-            vel_y *= (std::f32::consts::PI / 4.).cos();
-        }
+        // proportional with a stick, diagonals are normalized with keys
+        let axis = action_state.movement();
+        let moving = axis != Vec2::ZERO;
 
+        let previous_velocity = rb_vel.linvel;
         // rb_vel.linvel.x = x_axis as f32 * **speed * 200. * time.delta_seconds();
-        rb_vel.linvel.x = vel_x;
-        rb_vel.linvel.y = vel_y;
+        rb_vel.linvel = match movement_feel {
+            Some(movement_feel) => movement_feel.velocity(
+                rb_vel.linvel,
+                axis,
+                **speed,
+                game_time.delta_for(frozen).as_secs_f32(),
+            ),
+            None => axis * **speed,
+        };
 
         /* -------------------------------------------------------------------------- */
         /*                                  Animation                                 */
         /* -------------------------------------------------------------------------- */
 
-        // if there is any movement
-        if (left || right || up || down) && *player_state != CharacterState::Run {
-            *player_state = CharacterState::Run;
-        } else if !(left || right || up || down)
-            && *player_state == CharacterState::Run
-            && *player_state != CharacterState::Idle
-        {
-            // IDEA: Polish #visual - When we reach max speed (one full run loop), whenever you stop there is a smoke anim (sudden braking)
-            *player_state = CharacterState::Idle;
-        }
-
-        /* -------------------------------------------------------------------------- */
-        /*                                  Direction                                 */
-        /* -------------------------------------------------------------------------- */
-
-        if !(left && right) {
-            if right {
-                texture_atlas_sprite.flip_x = false;
-            } else if left {
-                texture_atlas_sprite.flip_x = true;
+        // actions (ie. shooting) play their animation until the end
+        let locomotion = matches!(*player_state, CharacterState::Idle | CharacterState::Run);
+
+        if locomotion {
+            // if there is any movement
+            if moving && *player_state != CharacterState::Run {
+                *player_state = CharacterState::Run;
+            } else if !moving
+                && *player_state == CharacterState::Run
+                && *player_state != CharacterState::Idle
+            {
+                // sudden braking
+                if previous_velocity.length() >= PLAYER_SKID_SPEED_RATIO * **speed {
+                    skid_events.send(Skidded {
+                        entity: player,
+                        velocity: previous_velocity,
+                    });
+                }
+                *player_state = CharacterState::Idle;
             }
         }
     }
 }
 
 fn camera_follow(
+    juice_settings: Res<JuiceSettings>,
     mut query: ParamSet<(
         Query<&Transform, With<Player>>,
         Query<&mut Transform, With<PlayerCamera>>,
@@ -113,12 +135,29 @@ fn camera_follow(
                     player_transform.translation.y,
                     camera_transform.translation.z,
                 ),
-                CAMERA_INTERPOLATION,
+                (CAMERA_INTERPOLATION * juice_settings.camera_lerp.intensity).clamp(0., 1.),
             );
         }
     }
 }
 
+/// [`camera_follow`] without the lerp.
+fn camera_snap(
+    mut query: ParamSet<(
+        Query<&Transform, With<Player>>,
+        Query<&mut Transform, With<PlayerCamera>>,
+    )>,
+) {
+    if let Ok(t) = query.p0().get_single() {
+        let player_transform = *t;
+
+        if let Ok(mut camera_transform) = query.p1().get_single_mut() {
+            camera_transform.translation.x = player_transform.translation.x;
+            camera_transform.translation.y = player_transform.translation.y;
+        }
+    }
+}
+
 fn spawn_player(mut commands: Commands, characters_spritesheet: Res<CharacterSpriteSheet>) {
     /* -------------------------------------------------------------------------- */
     /*                              Animation Indices                             */
@@ -127,12 +166,23 @@ fn spawn_player(mut commands: Commands, characters_spritesheet: Res<CharacterSpr
     let mut animation_indices = AnimationIndices(HashMap::new());
     animation_indices.insert(CharacterState::Idle, PLAYER_IDLE_FRAMES);
     animation_indices.insert(CharacterState::Run, PLAYER_RUN_FRAMES);
+    animation_indices.insert(CharacterState::Shoot, PLAYER_SHOOT_FRAMES);
+    animation_indices.insert(CharacterState::Dash, PLAYER_DASH_FRAMES);
+    animation_indices.insert(CharacterState::Melee, PLAYER_MELEE_FRAMES);
+    animation_indices.insert(CharacterState::Hit, PLAYER_HIT_FRAMES);
+    animation_indices.insert(CharacterState::Death, PLAYER_DEATH_FRAMES);
+
+    let mut animation_notifies = AnimationNotifies(HashMap::new());
+    animation_notifies.insert(CharacterState::Run, PLAYER_RUN_NOTIFIES.to_vec());
+    animation_notifies.insert(CharacterState::Shoot, PLAYER_SHOOT_NOTIFIES.to_vec());
+    animation_notifies.insert(CharacterState::Melee, PLAYER_MELEE_NOTIFIES.to_vec());
 
     commands
         .spawn((
             SpriteSheetBundle {
                 texture_atlas: characters_spritesheet.texture_atlas.clone(),
-                transform: Transform::from_scale(Vec3::splat(PLAYER_SCALE)),
+                transform: Transform::from_xyz(0., 0., CHARACTERS_Z)
+                    .with_scale(Vec3::splat(PLAYER_SCALE)),
                 ..default()
             },
             Name::new("Player"),
@@ -143,6 +193,20 @@ fn spawn_player(mut commands: Commands, characters_spritesheet: Res<CharacterSpr
                 speed: Speed(100. * TILE_SIZE),
                 ..default()
             },
+            animation_notifies,
+            MovementFeel::default(),
+            Dash::default(),
+            Weapon::default(),
+            MeleeAttack::default(),
+            Aim::default(),
+            Health::new(PLAYER_HEALTH)
+                .with_invulnerability(PLAYER_INVULNERABILITY)
+                .with_corpse(),
+            HitFlash {
+                color: RED_FLASH,
+                ..default()
+            },
+            SquashStretch::default(),
             // -- Hitbox --
             RigidBody::Dynamic,
             LockedAxes::ROTATION_LOCKED,
@@ -0,0 +1,140 @@
+//! Aim
+//!
+//! The player aims at the mouse cursor, or with the right stick on a gamepad
+//! (whichever was used last), and faces its [`Aim`] whatever the movement direction.
+//! A [`Reticle`] shows the aimed point.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    characters::player::Player,
+    constants::character::player::RETICLE_DISTANCE,
+    controls::{actions::ActionState, gamepad::LastInputDevice},
+    playing, PlayerCamera,
+};
+
+const RETICLE_COLOR: Color = Color::rgba(1., 1., 1., 0.8);
+/// Length of a branch of the cross.
+const RETICLE_SIZE: f32 = 1.5;
+/// Between the center and the branches.
+const RETICLE_GAP: f32 = 1.;
+
+pub struct AimPlugin;
+
+impl Plugin for AimPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_reticle).add_systems(
+            Update,
+            (update_aim.in_set(AimSet), face_aim.after(AimSet)).run_if(playing),
+        );
+    }
+}
+
+/// Systems reading the [`Aim`] run after this set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct AimSet;
+
+/// Where a character aims, normalized.
+#[derive(Debug, Clone, Copy, Deref, DerefMut, Reflect, Component)]
+#[reflect(Component)]
+pub struct Aim(pub Vec2);
+
+impl Default for Aim {
+    fn default() -> Self {
+        Aim(Vec2::X)
+    }
+}
+
+#[derive(Component)]
+pub struct Reticle;
+
+fn spawn_reticle(mut commands: Commands) {
+    let branch = |offset: Vec2, size: Vec2| SpriteBundle {
+        sprite: Sprite {
+            color: RETICLE_COLOR,
+            custom_size: Some(size),
+            ..default()
+        },
+        transform: Transform::from_translation(offset.extend(0.)),
+        ..default()
+    };
+    let distance = RETICLE_GAP + RETICLE_SIZE / 2.;
+    let horizontal = Vec2::new(RETICLE_SIZE, 0.3);
+    let vertical = Vec2::new(0.3, RETICLE_SIZE);
+
+    commands
+        .spawn((
+            // above everything
+            SpatialBundle::from_transform(Transform::from_xyz(0., 0., 100.)),
+            Reticle,
+            Name::new("Reticle"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(branch(Vec2::new(-distance, 0.), horizontal));
+            parent.spawn(branch(Vec2::new(distance, 0.), horizontal));
+            parent.spawn(branch(Vec2::new(0., -distance), vertical));
+            parent.spawn(branch(Vec2::new(0., distance), vertical));
+        });
+}
+
+/// Aim at the cursor with the mouse, in the right stick's direction with a gamepad.
+pub fn update_aim(
+    action_state: Res<ActionState>,
+    last_input_device: Res<LastInputDevice>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &Transform), With<PlayerCamera>>,
+    mut player_query: Query<(&Transform, &mut Aim), With<Player>>,
+    mut reticle_query: Query<
+        &mut Transform,
+        (With<Reticle>, Without<Player>, Without<PlayerCamera>),
+    >,
+) {
+    let Ok((player_transform, mut aim)) = player_query.get_single_mut() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+
+    let target = match *last_input_device {
+        LastInputDevice::Keyboard => {
+            let (Ok(window), Ok((camera, camera_transform))) =
+                (window_query.get_single(), camera_query.get_single())
+            else {
+                return;
+            };
+            // the `GlobalTransform` still holds last frame's shake
+            let Some(cursor) = window.cursor_position().and_then(|cursor| {
+                camera.viewport_to_world_2d(&GlobalTransform::from(*camera_transform), cursor)
+            }) else {
+                return;
+            };
+
+            if let Some(direction) = (cursor - player_position).try_normalize() {
+                **aim = direction;
+            }
+            cursor
+        }
+        LastInputDevice::Gamepad => {
+            // keep the last direction when the stick is released
+            if let Some(direction) = action_state.aim().try_normalize() {
+                **aim = direction;
+            }
+            player_position + **aim * RETICLE_DISTANCE
+        }
+    };
+
+    if let Ok(mut reticle_transform) = reticle_query.get_single_mut() {
+        reticle_transform.translation.x = target.x;
+        reticle_transform.translation.y = target.y;
+    }
+}
+
+/// Characters look where they aim, even when walking backward.
+fn face_aim(mut character_query: Query<(&Aim, &mut TextureAtlasSprite), Changed<Aim>>) {
+    for (aim, mut sprite) in &mut character_query {
+        if aim.x > 0. {
+            sprite.flip_x = false;
+        } else if aim.x < 0. {
+            sprite.flip_x = true;
+        }
+    }
+}
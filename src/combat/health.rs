@@ -0,0 +1,445 @@
+//! Health
+//!
+//! Every [`Hit`] with some damage becomes a [`Damage`] to its target's [`Health`].
+//! A damaged character is staggered ([`CharacterState::Hit`]) and can't be hurt again
+//! for a short [`InvulnerabilityWindow`]; with no health left, it plays its
+//! [`CharacterState::Death`] animation, then is despawned or stays as a corpse.
+//! [`Damaged`] is sent for the damage actually taken,
+//! [`Died`] as soon as the health reaches zero.
+//!
+//! A [`Heal`] gives health back to the living, up to their max: [`Healed`] is sent
+//! for the health actually restored.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    animations::sprite_sheet_animation::{
+        jump_frame_character_state, AnimationFinished, AnimationIndices, CharacterState,
+    },
+    combat::Hit,
+    hit_stop::{Frozen, GameTime},
+    playing,
+};
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Damage>()
+            .add_event::<Damaged>()
+            .add_event::<Died>()
+            .add_event::<Heal>()
+            .add_event::<Healed>()
+            .add_systems(Update, tick_invulnerability.run_if(playing))
+            .add_systems(
+                PostUpdate,
+                // the stagger wins over any state set during `Update`
+                (damage_on_hit, apply_damage, apply_heal)
+                    .chain()
+                    .before(jump_frame_character_state),
+            )
+            // after every command targeting the dead (ie. a knockback)
+            .add_systems(Last, remove_dead);
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Component)]
+#[reflect(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+    /// in seconds, after being damaged
+    pub invulnerability: f32,
+    /// Stay on the last frame of the death animation instead of being despawned.
+    pub corpse: bool,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health {
+            current: max,
+            max,
+            invulnerability: 0.,
+            corpse: false,
+        }
+    }
+
+    pub fn with_invulnerability(mut self, invulnerability: f32) -> Self {
+        self.invulnerability = invulnerability;
+        self
+    }
+
+    pub fn with_corpse(mut self) -> Self {
+        self.corpse = true;
+        self
+    }
+}
+
+/// Take `amount` from the `target`'s [`Health`].
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Damage {
+    pub attacker: Option<Entity>,
+    pub target: Entity,
+    /// Where it landed, in world space, if anywhere in particular.
+    pub position: Option<Vec2>,
+    pub amount: f32,
+    /// A critical hit, already included in the amount.
+    pub crit: bool,
+}
+
+/// `target` lost `amount` health: the [`Damage`] wasn't ignored.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Damaged {
+    pub attacker: Option<Entity>,
+    pub target: Entity,
+    pub position: Option<Vec2>,
+    pub amount: f32,
+    pub crit: bool,
+}
+
+/// Give `amount` back to the `target`'s [`Health`].
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Heal {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+/// `target` regained `amount` health.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Healed {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+/// `entity`'s health just reached zero.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Died {
+    pub entity: Entity,
+    pub killer: Option<Entity>,
+}
+
+/// I-frames: the character can't be hurt.
+#[derive(Debug, Default, Reflect, Component)]
+#[reflect(Component)]
+pub struct Invulnerable;
+
+/// Like [`Invulnerable`], until the timer ends.
+#[derive(Debug, Component)]
+pub struct InvulnerabilityWindow(pub Timer);
+
+/// The character's health reached zero: it can't be hurt nor act anymore.
+#[derive(Debug, Default, Component)]
+pub struct Dead;
+
+fn damage_on_hit(mut hit_events: EventReader<Hit>, mut damage_events: EventWriter<Damage>) {
+    for hit in hit_events.iter() {
+        if hit.damage > 0. {
+            damage_events.send(Damage {
+                attacker: Some(hit.attacker),
+                target: hit.target,
+                position: Some(hit.position),
+                amount: hit.damage,
+                crit: hit.crit,
+            });
+        }
+    }
+}
+
+/// A burst (ie. a shotgun blast) lands whole: the window only starts the next frame.
+pub fn apply_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<Damage>,
+    mut damaged_events: EventWriter<Damaged>,
+    mut died_events: EventWriter<Died>,
+    mut health_query: Query<
+        (
+            &mut Health,
+            Option<&AnimationIndices>,
+            Option<&mut CharacterState>,
+            Option<&mut Velocity>,
+        ),
+        (
+            Without<Dead>,
+            Without<Invulnerable>,
+            Without<InvulnerabilityWindow>,
+        ),
+    >,
+) {
+    for Damage {
+        attacker,
+        target,
+        position,
+        amount,
+        crit,
+    } in damage_events.iter()
+    {
+        let Ok((mut health, indices, character_state, velocity)) = health_query.get_mut(*target)
+        else {
+            continue;
+        };
+        // already killed by a previous damage this frame
+        if health.current <= 0. {
+            continue;
+        }
+
+        let taken = amount.min(health.current);
+        health.current -= taken;
+        damaged_events.send(Damaged {
+            attacker: *attacker,
+            target: *target,
+            position: *position,
+            amount: taken,
+            crit: *crit,
+        });
+
+        let new_state = if health.current > 0. {
+            if health.invulnerability > 0. {
+                commands
+                    .entity(*target)
+                    .insert(InvulnerabilityWindow(Timer::from_seconds(
+                        health.invulnerability,
+                        TimerMode::Once,
+                    )));
+            }
+            CharacterState::Hit
+        } else {
+            if let Some(mut velocity) = velocity {
+                velocity.linvel = Vec2::ZERO;
+            }
+            commands.entity(*target).insert(Dead);
+            died_events.send(Died {
+                entity: *target,
+                killer: *attacker,
+            });
+            CharacterState::Death
+        };
+
+        // characters without the animation keep their state
+        if let (Some(indices), Some(mut character_state)) = (indices, character_state) {
+            if indices.contains_key(&new_state) {
+                *character_state = new_state;
+            }
+        }
+    }
+}
+
+/// The dead can't be healed.
+pub fn apply_heal(
+    mut heal_events: EventReader<Heal>,
+    mut healed_events: EventWriter<Healed>,
+    mut health_query: Query<&mut Health, Without<Dead>>,
+) {
+    for Heal { target, amount } in heal_events.iter() {
+        let Ok(mut health) = health_query.get_mut(*target) else {
+            continue;
+        };
+
+        let restored = amount.min(health.max - health.current);
+        if restored <= 0. {
+            continue;
+        }
+        health.current += restored;
+        healed_events.send(Healed {
+            target: *target,
+            amount: restored,
+        });
+    }
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    mut window_query: Query<(Entity, &mut InvulnerabilityWindow, Option<&Frozen>)>,
+) {
+    for (entity, mut window, frozen) in &mut window_query {
+        window.0.tick(game_time.delta_for(frozen));
+        if window.0.finished() {
+            commands.entity(entity).remove::<InvulnerabilityWindow>();
+        }
+    }
+}
+
+/// Despawn the dead once their death animation is over (if any), unless they leave a corpse.
+fn remove_dead(
+    mut commands: Commands,
+    mut finished_events: EventReader<AnimationFinished>,
+    dead_query: Query<(&Health, Option<&AnimationIndices>), With<Dead>>,
+    new_dead_query: Query<Entity, Added<Dead>>,
+) {
+    let died_without_animation = new_dead_query.iter().filter(|dead| {
+        dead_query.get(*dead).map_or(false, |(_, indices)| {
+            !indices.map_or(false, |indices| {
+                indices.contains_key(&CharacterState::Death)
+            })
+        })
+    });
+    let death_animation_over = finished_events
+        .iter()
+        .filter(|finished| finished.state == CharacterState::Death)
+        .map(|finished| finished.entity);
+
+    for dead in died_without_animation.chain(death_animation_over) {
+        if let Ok((health, _)) = dead_query.get(dead) {
+            if !health.corpse {
+                commands.entity(dead).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn health_app() -> App {
+        let mut app = App::new();
+        app.add_event::<Damage>()
+            .add_event::<Damaged>()
+            .add_event::<Died>()
+            .add_event::<Heal>()
+            .add_event::<Healed>()
+            .add_event::<AnimationFinished>()
+            .add_systems(Update, (apply_damage, apply_heal).chain())
+            .add_systems(Last, remove_dead);
+        app
+    }
+
+    fn damage(app: &mut App, target: Entity, amount: f32) {
+        app.world.send_event(Damage {
+            attacker: None,
+            target,
+            position: None,
+            amount,
+            crit: false,
+        });
+    }
+
+    /// The events sent since the last call with this `reader`.
+    fn read<E: Event + Copy>(app: &App, reader: &mut ManualEventReader<E>) -> Vec<E> {
+        reader
+            .iter(app.world.resource::<Events<E>>())
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn burst_lands_whole_then_window_ignores_hits() {
+        let mut app = health_app();
+        let target = app
+            .world
+            .spawn(Health::new(100.).with_invulnerability(0.5))
+            .id();
+        let mut damaged_reader = app.world.resource::<Events<Damaged>>().get_reader();
+
+        for _ in 0..3 {
+            damage(&mut app, target, 10.);
+        }
+        app.update();
+
+        assert_eq!(read(&app, &mut damaged_reader).len(), 3);
+        assert_eq!(app.world.get::<Health>(target).unwrap().current, 70.);
+        assert!(app.world.get::<InvulnerabilityWindow>(target).is_some());
+
+        damage(&mut app, target, 10.);
+        app.update();
+
+        assert!(read(&app, &mut damaged_reader).is_empty());
+        assert_eq!(app.world.get::<Health>(target).unwrap().current, 70.);
+    }
+
+    #[test]
+    fn died_is_sent_once() {
+        let mut app = health_app();
+        let target = app.world.spawn(Health::new(10.).with_corpse()).id();
+        let mut damaged_reader = app.world.resource::<Events<Damaged>>().get_reader();
+        let mut died_reader = app.world.resource::<Events<Died>>().get_reader();
+
+        damage(&mut app, target, 20.);
+        damage(&mut app, target, 20.);
+        app.update();
+
+        let damaged = read(&app, &mut damaged_reader);
+        assert_eq!(damaged.len(), 1);
+        assert_eq!(damaged[0].amount, 10.);
+        assert_eq!(read(&app, &mut died_reader).len(), 1);
+
+        damage(&mut app, target, 20.);
+        app.update();
+
+        assert!(read(&app, &mut damaged_reader).is_empty());
+        assert!(read(&app, &mut died_reader).is_empty());
+    }
+
+    #[test]
+    fn dead_are_despawned_unless_corpses() {
+        let mut app = health_app();
+        let despawned = app.world.spawn(Health::new(10.)).id();
+        let corpse = app.world.spawn(Health::new(10.).with_corpse()).id();
+
+        damage(&mut app, despawned, 10.);
+        damage(&mut app, corpse, 10.);
+        app.update();
+
+        assert!(app.world.get_entity(despawned).is_none());
+        assert!(app.world.get::<Dead>(corpse).is_some());
+    }
+
+    #[test]
+    fn dead_are_despawned_after_their_death_animation() {
+        let mut app = health_app();
+        let target = app
+            .world
+            .spawn((
+                Health::new(10.),
+                CharacterState::Idle,
+                AnimationIndices(HashMap::from([
+                    (CharacterState::Idle, (0, 3, CharacterState::Idle)),
+                    (CharacterState::Death, (4, 7, CharacterState::Death)),
+                ])),
+            ))
+            .id();
+
+        damage(&mut app, target, 10.);
+        app.update();
+
+        assert_eq!(
+            *app.world.get::<CharacterState>(target).unwrap(),
+            CharacterState::Death
+        );
+
+        app.world.send_event(AnimationFinished {
+            entity: target,
+            state: CharacterState::Death,
+        });
+        app.update();
+
+        assert!(app.world.get_entity(target).is_none());
+    }
+
+    #[test]
+    fn heal_stops_at_max_and_skips_the_dead() {
+        let mut app = health_app();
+        let target = app.world.spawn(Health::new(10.)).id();
+        let corpse = app.world.spawn(Health::new(10.).with_corpse()).id();
+        let mut healed_reader = app.world.resource::<Events<Healed>>().get_reader();
+
+        damage(&mut app, target, 4.);
+        damage(&mut app, corpse, 10.);
+        app.update();
+
+        for target in [target, corpse] {
+            app.world.send_event(Heal { target, amount: 6. });
+        }
+        app.update();
+
+        let healed = read(&app, &mut healed_reader);
+        assert_eq!(healed.len(), 1);
+        assert_eq!(healed[0].amount, 4.);
+        assert_eq!(app.world.get::<Health>(target).unwrap().current, 10.);
+        assert_eq!(app.world.get::<Health>(corpse).unwrap().current, 0.);
+    }
+}
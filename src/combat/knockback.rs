@@ -0,0 +1,164 @@
+//! Knockback
+//!
+//! A [`Knockback`] pushes an entity with a decaying impulse (ie. recoil),
+//! every [`Hit`] sends one to its target.
+//!
+//! Drivers like `player_movement` overwrite the velocity every frame,
+//! so the impulse lives on top of it, like the camera shake on top of the camera follow:
+//! it is removed before any `Update` system reads the velocity,
+//! and added back just before the physics step.
+//!
+//! Sent to the [`PlayerCamera`](crate::PlayerCamera), it kicks the camera instead
+//! (see [`CameraShake`](crate::camera::CameraShake)).
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    combat::Hit,
+    constants::weapons::KNOCKBACK_DECAY,
+    hit_stop::{freeze_velocity, Frozen, GameTime},
+};
+
+pub struct KnockbackPlugin;
+
+impl Plugin for KnockbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Knockback>()
+            .add_systems(PreUpdate, remove_knockback)
+            .add_systems(
+                PostUpdate,
+                (knockback_on_hit, add_knockback, apply_knockback)
+                    .chain()
+                    .after(freeze_velocity)
+                    .before(PhysicsSet::SyncBackend),
+            );
+    }
+}
+
+/// Push `target` with `impulse` (a velocity) which then decays.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Knockback {
+    pub target: Entity,
+    pub impulse: Vec2,
+    /// Exponential decay rate, per second.
+    pub decay: f32,
+}
+
+impl Knockback {
+    pub fn new(target: Entity, impulse: Vec2) -> Self {
+        Knockback {
+            target,
+            impulse,
+            decay: KNOCKBACK_DECAY,
+        }
+    }
+
+    /// Higher is shorter.
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    /// The distance the impulse pushes before fading out.
+    pub fn distance(&self) -> Vec2 {
+        self.impulse / self.decay.max(f32::EPSILON)
+    }
+}
+
+/// The remaining knockback of an entity.
+#[derive(Debug, Default, Reflect, Component)]
+#[reflect(Component)]
+pub struct KnockbackVelocity {
+    pub velocity: Vec2,
+    /// Exponential decay rate, per second.
+    pub decay: f32,
+    /// Velocity added the last frame, to be removed the next one.
+    applied: Vec2,
+    /// The velocity right before the physics step, to know what collisions took away.
+    stepped: Vec2,
+}
+
+fn knockback_on_hit(
+    mut hit_events: EventReader<Hit>,
+    mut knockback_events: EventWriter<Knockback>,
+) {
+    for hit in hit_events.iter() {
+        if hit.knockback != Vec2::ZERO {
+            knockback_events.send(Knockback::new(hit.target, hit.knockback));
+        }
+    }
+}
+
+fn add_knockback(
+    mut commands: Commands,
+    mut knockback_events: EventReader<Knockback>,
+    mut knockback_query: Query<&mut KnockbackVelocity>,
+    body_query: Query<(), With<Velocity>>,
+) {
+    // several knockbacks can target a new entity the same frame
+    let mut new_knockbacks = HashMap::<Entity, KnockbackVelocity>::new();
+
+    for Knockback {
+        target,
+        impulse,
+        decay,
+    } in knockback_events.iter()
+    {
+        if let Ok(mut knockback) = knockback_query.get_mut(*target) {
+            knockback.velocity += *impulse;
+            knockback.decay = *decay;
+        } else if body_query.contains(*target) {
+            let knockback = new_knockbacks.entry(*target).or_default();
+            knockback.velocity += *impulse;
+            knockback.decay = *decay;
+        }
+    }
+
+    for (target, knockback) in new_knockbacks {
+        commands.entity(target).insert(knockback);
+    }
+}
+
+/// Decay the knockback and add it to the velocity set by the driver (if any).
+fn apply_knockback(
+    game_time: Res<GameTime>,
+    mut knockback_query: Query<(&mut KnockbackVelocity, &mut Velocity, Option<&Frozen>)>,
+) {
+    for (mut knockback, mut velocity, frozen) in &mut knockback_query {
+        let delta = game_time.delta_for(frozen).as_secs_f32();
+        knockback.velocity *= (-knockback.decay * delta).exp();
+        if knockback.velocity.length_squared() < 0.01 {
+            knockback.velocity = Vec2::ZERO;
+        }
+
+        // frozen entities move at their time scale
+        let applied = knockback.velocity * frozen.map_or(1., |frozen| frozen.time_scale);
+        velocity.linvel += applied;
+        knockback.applied = applied;
+        knockback.stepped = velocity.linvel;
+    }
+}
+
+/// Give the driver its own velocity back.
+fn remove_knockback(
+    mut commands: Commands,
+    mut knockback_query: Query<(Entity, &mut KnockbackVelocity, &mut Velocity)>,
+) {
+    for (entity, mut knockback, mut velocity) in &mut knockback_query {
+        let applied_speed = knockback.applied.length();
+        if applied_speed > 0. {
+            // a wall may have stopped part of the knockback: don't bounce off it
+            let direction = knockback.applied / applied_speed;
+            let stopped = (knockback.stepped - velocity.linvel)
+                .dot(direction)
+                .clamp(0., applied_speed);
+            velocity.linvel -= direction * (applied_speed - stopped);
+        }
+        knockback.applied = Vec2::ZERO;
+
+        if knockback.velocity == Vec2::ZERO {
+            commands.entity(entity).remove::<KnockbackVelocity>();
+        }
+    }
+}
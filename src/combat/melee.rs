@@ -0,0 +1,235 @@
+//! Melee
+//!
+//! A [`MeleeAttack`] swings on [`Action::Melee`]. Its hitbox, a sensor child of the character,
+//! is only enabled between the [`AnimationTag::HitboxStart`] and [`AnimationTag::HitboxEnd`]
+//! notifies of the swing animation, and [`Hit`]s each body it overlaps once per swing.
+
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    animations::sprite_sheet_animation::{
+        AnimationEvent, AnimationFinished, AnimationStarted, AnimationTag, CharacterState,
+    },
+    characters::{
+        dash::Dashing,
+        player::{player_movement, Player},
+    },
+    combat::{health::Dead, Hit},
+    constants::character::player::{
+        PLAYER_MELEE_DAMAGE, PLAYER_MELEE_HITBOX, PLAYER_MELEE_HITBOX_OFFSET,
+        PLAYER_MELEE_KNOCKBACK,
+    },
+    controls::{actions::Action, buffer::InputBuffer},
+    map::Wall,
+    playing,
+};
+
+pub struct MeleePlugin;
+
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                spawn_melee_hitboxes,
+                start_melee.before(player_movement),
+                (toggle_melee_hitboxes, melee_hits).chain(),
+            )
+                .run_if(playing),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Component)]
+#[reflect(Component)]
+pub struct MeleeAttack {
+    /// half extents
+    pub hitbox: Vec2,
+    /// from the character's center, facing right
+    pub hitbox_offset: Vec2,
+    /// Velocity given to the target, away from the character.
+    pub knockback: f32,
+    pub damage: f32,
+    /// Bodies already hit by the current swing.
+    #[reflect(ignore)]
+    hit: HashSet<Entity>,
+}
+
+impl Default for MeleeAttack {
+    fn default() -> Self {
+        MeleeAttack {
+            hitbox: Vec2::new(PLAYER_MELEE_HITBOX.0, PLAYER_MELEE_HITBOX.1),
+            hitbox_offset: Vec2::new(PLAYER_MELEE_HITBOX_OFFSET.0, PLAYER_MELEE_HITBOX_OFFSET.1),
+            knockback: PLAYER_MELEE_KNOCKBACK,
+            damage: PLAYER_MELEE_DAMAGE,
+            hit: HashSet::default(),
+        }
+    }
+}
+
+/// The sensor of a [`MeleeAttack`], disabled outside of its active frames.
+#[derive(Debug, Component)]
+pub struct MeleeHitbox {
+    pub owner: Entity,
+}
+
+fn spawn_melee_hitboxes(
+    mut commands: Commands,
+    melee_query: Query<(Entity, &MeleeAttack, &Transform), Added<MeleeAttack>>,
+) {
+    for (character, melee_attack, transform) in &melee_query {
+        let hitbox = commands
+            .spawn((
+                MeleeHitbox { owner: character },
+                Collider::cuboid(melee_attack.hitbox.x, melee_attack.hitbox.y),
+                Sensor,
+                // not deformed by the squash & stretch of the character
+                ColliderScale::Absolute(transform.scale.truncate()),
+                // don't weigh on the character
+                ColliderMassProperties::Density(0.),
+                ColliderDisabled,
+                TransformBundle::from_transform(Transform::from_translation(
+                    melee_attack.hitbox_offset.extend(0.),
+                )),
+                Name::new("Melee Hitbox"),
+            ))
+            .id();
+        commands.entity(character).add_child(hitbox);
+    }
+}
+
+fn start_melee(
+    mut input_buffer: ResMut<InputBuffer>,
+    mut player_query: Query<
+        (&mut MeleeAttack, &mut CharacterState),
+        (With<Player>, Without<Dashing>, Without<Dead>),
+    >,
+) {
+    let Ok((mut melee_attack, mut player_state)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    // cancels a shot's animation, not a swing
+    if !matches!(
+        *player_state,
+        CharacterState::Idle | CharacterState::Run | CharacterState::Shoot
+    ) {
+        return;
+    }
+    if !input_buffer.consume_buffered(Action::Melee) {
+        return;
+    }
+
+    melee_attack.hit.clear();
+    *player_state = CharacterState::Melee;
+}
+
+/// Enable the hitbox on the active frames of the swing, in front of the character.
+///
+/// Any new animation ends the swing (ie. a dash).
+fn toggle_melee_hitboxes(
+    mut commands: Commands,
+    mut started_events: EventReader<AnimationStarted>,
+    mut finished_events: EventReader<AnimationFinished>,
+    mut animation_events: EventReader<AnimationEvent>,
+    character_query: Query<(&MeleeAttack, &TextureAtlasSprite)>,
+    mut hitbox_query: Query<(Entity, &MeleeHitbox, &mut Transform)>,
+) {
+    // the swing's tags come after its start
+    let toggles = started_events
+        .iter()
+        .map(|started| (started.entity, false))
+        .chain(
+            finished_events
+                .iter()
+                .map(|finished| (finished.entity, false)),
+        )
+        .chain(
+            animation_events
+                .iter()
+                .filter(|event| event.state == CharacterState::Melee)
+                .filter_map(|event| match event.tag {
+                    AnimationTag::HitboxStart => Some((event.entity, true)),
+                    AnimationTag::HitboxEnd => Some((event.entity, false)),
+                    _ => None,
+                }),
+        );
+
+    for (character, active) in toggles {
+        let Ok((melee_attack, sprite)) = character_query.get(character) else {
+            continue;
+        };
+
+        for (hitbox, _, mut transform) in hitbox_query
+            .iter_mut()
+            .filter(|(_, melee_hitbox, _)| melee_hitbox.owner == character)
+        {
+            if active {
+                let facing = if sprite.flip_x { -1. } else { 1. };
+                transform.translation.x = melee_attack.hitbox_offset.x * facing;
+                transform.translation.y = melee_attack.hitbox_offset.y;
+                commands.entity(hitbox).remove::<ColliderDisabled>();
+            } else {
+                commands.entity(hitbox).insert(ColliderDisabled);
+            }
+        }
+    }
+}
+
+/// Each body overlapping an active hitbox is hit once per swing.
+fn melee_hits(
+    rapier_context: Res<RapierContext>,
+    mut hit_events: EventWriter<Hit>,
+    hitbox_query: Query<(Entity, &MeleeHitbox), Without<ColliderDisabled>>,
+    mut character_query: Query<(&mut MeleeAttack, &GlobalTransform)>,
+    body_query: Query<&GlobalTransform>,
+    wall_query: Query<(), With<Wall>>,
+) {
+    for (hitbox, melee_hitbox) in &hitbox_query {
+        let Ok((mut melee_attack, character_transform)) =
+            character_query.get_mut(melee_hitbox.owner)
+        else {
+            continue;
+        };
+
+        for (e1, e2, intersecting) in rapier_context.intersections_with(hitbox) {
+            if !intersecting {
+                continue;
+            }
+            let other = if e1 == hitbox { e2 } else { e1 };
+            if wall_query.contains(other) {
+                continue;
+            }
+            let Some(body) = rapier_context.collider_parent(other) else {
+                continue;
+            };
+            if body == melee_hitbox.owner || !melee_attack.hit.insert(body) {
+                continue;
+            }
+
+            // away from the character
+            let direction = body_query.get(body).map_or(Vec2::ZERO, |body_transform| {
+                (body_transform.translation() - character_transform.translation())
+                    .truncate()
+                    .normalize_or_zero()
+            });
+            // on the hurtbox
+            let position = body_query
+                .get(other)
+                .map_or(
+                    character_transform.translation(),
+                    GlobalTransform::translation,
+                )
+                .truncate();
+            hit_events.send(Hit {
+                attacker: melee_hitbox.owner,
+                target: body,
+                position,
+                knockback: direction * melee_attack.knockback,
+                damage: melee_attack.damage,
+                crit: false,
+            });
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+
+pub mod aim;
+pub mod health;
+pub mod knockback;
+pub mod melee;
+pub mod shoot;
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Hit>().add_plugins((
+            aim::AimPlugin,
+            health::HealthPlugin,
+            knockback::KnockbackPlugin,
+            melee::MeleePlugin,
+            shoot::ShootPlugin,
+        ));
+    }
+}
+
+/// An attack (ie. a pellet, a swing) landed on `target`, a body.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Hit {
+    pub attacker: Entity,
+    pub target: Entity,
+    /// Where it landed, in world space.
+    pub position: Vec2,
+    /// Velocity given to the target.
+    pub knockback: Vec2,
+    /// Taken from the target's [`Health`](health::Health), if any.
+    pub damage: f32,
+    /// A critical hit, already included in the damage.
+    pub crit: bool,
+}
@@ -0,0 +1,295 @@
+//! Shoot
+//!
+//! A [`Weapon`] fires a spread of [`Pellet`]s towards the [`Aim`],
+//! at its fire rate while [`Action::Shoot`] is held.
+//! Pellets are kinematic sensors: they fly straight until they hit a [`Wall`] or expire.
+//! Every shot pushes the shooter back and kicks the camera through [`Knockback`]s,
+//! every pellet [`Hit`]s the body it touches, or leaves a [`WallHit`].
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    animations::sprite_sheet_animation::CharacterState,
+    characters::{
+        dash::Dashing,
+        player::{player_movement, Player},
+    },
+    collisions::CollisionEventExt,
+    combat::{
+        aim::{Aim, AimSet},
+        health::Dead,
+        knockback::Knockback,
+        Hit,
+    },
+    constants::weapons::{
+        PELLET_RADIUS, SHOTGUN_CAMERA_KICK, SHOTGUN_CRIT_MULTIPLIER, SHOTGUN_DAMAGE,
+        SHOTGUN_FIRE_RATE, SHOTGUN_KNOCKBACK, SHOTGUN_MUZZLE_OFFSET, SHOTGUN_PELLET_COUNT,
+        SHOTGUN_PELLET_LIFETIME, SHOTGUN_PELLET_SPEED, SHOTGUN_POINT_BLANK, SHOTGUN_RECOIL,
+        SHOTGUN_RECOIL_DECAY, SHOTGUN_SPREAD,
+    },
+    controls::{
+        actions::{Action, ActionState},
+        buffer::InputBuffer,
+    },
+    hit_stop::{Frozen, GameTime},
+    map::Wall,
+    playing, PlayerCamera,
+};
+
+const PELLET_COLOR: Color = Color::rgb(1., 0.85, 0.4);
+
+pub struct ShootPlugin;
+
+impl Plugin for ShootPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WallHit>().add_systems(
+            Update,
+            (
+                (tick_weapon_cooldown, shoot)
+                    .chain()
+                    .after(AimSet)
+                    .before(player_movement),
+                (pellet_collisions, pellet_lifetime).chain(),
+            )
+                .run_if(playing),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Component)]
+#[reflect(Component)]
+pub struct Weapon {
+    pub pellet_count: usize,
+    /// in radian, between the two outer pellets
+    pub spread: f32,
+    pub pellet_speed: f32,
+    /// shots per second
+    pub fire_rate: f32,
+    /// in seconds
+    pub pellet_lifetime: f32,
+    /// from the character's center, facing right
+    pub muzzle_offset: Vec2,
+    /// Velocity pushing the shooter back.
+    pub recoil: f32,
+    /// Exponential decay of the recoil, per second.
+    pub recoil_decay: f32,
+    /// Velocity given to the target of each pellet.
+    pub knockback: f32,
+    /// per pellet
+    pub damage: f32,
+    /// in seconds of flight: the pellets hitting sooner crit
+    pub point_blank: f32,
+    /// Of the damage of a crit.
+    pub crit_multiplier: f32,
+    /// Velocity kicking the camera, opposite to the shot.
+    pub camera_kick: f32,
+    cooldown: Timer,
+}
+
+impl Default for Weapon {
+    /// The shotgun.
+    fn default() -> Self {
+        Weapon {
+            pellet_count: SHOTGUN_PELLET_COUNT,
+            spread: SHOTGUN_SPREAD,
+            pellet_speed: SHOTGUN_PELLET_SPEED,
+            fire_rate: SHOTGUN_FIRE_RATE,
+            pellet_lifetime: SHOTGUN_PELLET_LIFETIME,
+            muzzle_offset: Vec2::new(SHOTGUN_MUZZLE_OFFSET.0, SHOTGUN_MUZZLE_OFFSET.1),
+            recoil: SHOTGUN_RECOIL,
+            recoil_decay: SHOTGUN_RECOIL_DECAY,
+            knockback: SHOTGUN_KNOCKBACK,
+            damage: SHOTGUN_DAMAGE,
+            point_blank: SHOTGUN_POINT_BLANK,
+            crit_multiplier: SHOTGUN_CRIT_MULTIPLIER,
+            camera_kick: SHOTGUN_CAMERA_KICK,
+            cooldown: Timer::default(),
+        }
+    }
+}
+
+impl Weapon {
+    /// The direction of each pellet, evenly spread around `direction`.
+    pub fn pellet_directions(&self, direction: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.pellet_count).map(move |i| {
+            let angle = if self.pellet_count > 1 {
+                -self.spread / 2. + self.spread * i as f32 / (self.pellet_count - 1) as f32
+            } else {
+                0.
+            };
+            Vec2::from_angle(angle).rotate(direction)
+        })
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct Pellet {
+    pub shooter: Entity,
+    /// Given to what the pellet hits.
+    pub knockback: Vec2,
+    pub damage: f32,
+    /// in seconds of flight, see [`Weapon::point_blank`]
+    pub point_blank: f32,
+    pub crit_multiplier: f32,
+    lifetime: Timer,
+}
+
+/// A pellet stopped against a wall.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct WallHit {
+    pub position: Vec2,
+    /// Of the pellet.
+    pub direction: Vec2,
+}
+
+fn tick_weapon_cooldown(
+    game_time: Res<GameTime>,
+    mut weapon_query: Query<(&mut Weapon, Option<&Frozen>)>,
+) {
+    for (mut weapon, frozen) in &mut weapon_query {
+        weapon.cooldown.tick(game_time.delta_for(frozen));
+    }
+}
+
+fn shoot(
+    mut commands: Commands,
+    action_state: Res<ActionState>,
+    mut input_buffer: ResMut<InputBuffer>,
+    mut knockback_events: EventWriter<Knockback>,
+    mut player_query: Query<
+        (Entity, &mut Weapon, &Transform, &Aim, &mut CharacterState),
+        (With<Player>, Without<Dashing>, Without<Dead>),
+    >,
+    camera_query: Query<Entity, With<PlayerCamera>>,
+) {
+    let Ok((player, mut weapon, transform, aim, mut player_state)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    if !weapon.cooldown.finished() || weapon.fire_rate <= 0. {
+        return;
+    }
+    // a swing or a stagger plays until the end
+    if matches!(*player_state, CharacterState::Melee | CharacterState::Hit) {
+        return;
+    }
+    if !input_buffer.consume_buffered(Action::Shoot) && !action_state.pressed(Action::Shoot) {
+        return;
+    }
+
+    let direction = **aim;
+    let muzzle = transform.translation.truncate()
+        + direction * weapon.muzzle_offset.x
+        + Vec2::Y * weapon.muzzle_offset.y;
+
+    for pellet_direction in weapon.pellet_directions(direction) {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: PELLET_COLOR,
+                    custom_size: Some(Vec2::splat(PELLET_RADIUS * 2.)),
+                    ..default()
+                },
+                transform: Transform::from_translation(muzzle.extend(transform.translation.z)),
+                ..default()
+            },
+            Pellet {
+                shooter: player,
+                knockback: pellet_direction * weapon.knockback,
+                damage: weapon.damage,
+                point_blank: weapon.point_blank,
+                crit_multiplier: weapon.crit_multiplier,
+                lifetime: Timer::from_seconds(weapon.pellet_lifetime, TimerMode::Once),
+            },
+            RigidBody::KinematicVelocityBased,
+            Velocity::linear(pellet_direction * weapon.pellet_speed),
+            Collider::ball(PELLET_RADIUS),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+            Name::new("Pellet"),
+        ));
+    }
+
+    knockback_events
+        .send(Knockback::new(player, -direction * weapon.recoil).with_decay(weapon.recoil_decay));
+    if let Ok(camera) = camera_query.get_single() {
+        knockback_events.send(Knockback::new(camera, -direction * weapon.camera_kick));
+    }
+
+    let cooldown = 1. / weapon.fire_rate;
+    weapon.cooldown = Timer::from_seconds(cooldown, TimerMode::Once);
+    *player_state = CharacterState::Shoot;
+}
+
+fn pellet_lifetime(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    mut pellet_query: Query<(Entity, &mut Pellet, Option<&Frozen>)>,
+) {
+    for (entity, mut pellet, frozen) in &mut pellet_query {
+        pellet.lifetime.tick(game_time.delta_for(frozen));
+        if pellet.lifetime.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Pellets expire when they hit a wall or a body.
+fn pellet_collisions(
+    rapier_context: Res<RapierContext>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut hit_events: EventWriter<Hit>,
+    mut wall_hit_events: EventWriter<WallHit>,
+    mut pellet_query: Query<(&mut Pellet, &Transform, &Velocity)>,
+    wall_query: Query<(), With<Wall>>,
+) {
+    for (e1, e2) in collision_events
+        .iter()
+        .filter(|event| event.is_started())
+        .map(CollisionEventExt::entities)
+    {
+        for (pellet, other) in [(e1, e2), (e2, e1)] {
+            let Ok((mut pellet, transform, velocity)) = pellet_query.get_mut(pellet) else {
+                continue;
+            };
+            // already spent on another collider
+            if pellet.lifetime.finished() {
+                continue;
+            }
+
+            if wall_query.contains(other) {
+                wall_hit_events.send(WallHit {
+                    position: transform.translation.truncate(),
+                    direction: velocity.linvel.normalize_or_zero(),
+                });
+            } else {
+                let Some(body) = rapier_context.collider_parent(other) else {
+                    continue;
+                };
+                if body == pellet.shooter {
+                    continue;
+                }
+                let crit = pellet.lifetime.elapsed_secs() <= pellet.point_blank;
+                hit_events.send(Hit {
+                    attacker: pellet.shooter,
+                    target: body,
+                    position: transform.translation.truncate(),
+                    knockback: pellet.knockback,
+                    damage: if crit {
+                        pellet.damage * pellet.crit_multiplier
+                    } else {
+                        pellet.damage
+                    },
+                    crit,
+                });
+            }
+
+            // despawned by `pellet_lifetime`
+            let remaining = pellet.lifetime.remaining();
+            pellet.lifetime.tick(remaining);
+        }
+    }
+}
@@ -3,6 +3,30 @@ pub const TILE_SIZE: f32 = 1.;
 
 pub const FRAME_TIME: f32 = 0.1;
 
+/// The depth of each layer of sprites, back to front.
+/// The camera sees from `-0.1`: the map background is at `0.`.
+pub mod layers {
+    /// Bullet holes, blood and scorch marks, just above the map background.
+    pub const DECALS_Z: f32 = 1.;
+    /// Dust and smoke on the floor, below the characters.
+    pub const PARTICLES_Z: f32 = 2.;
+    pub const CASINGS_Z: f32 = 3.;
+    pub const CHARACTERS_Z: f32 = 10.;
+    /// Damage numbers, above the characters and below the reticle.
+    pub const POPUPS_Z: f32 = 50.;
+}
+
+pub mod camera {
+    pub const CAMERA_SHAKE_MAX_OFFSET: f32 = 6.;
+    /// in radian
+    pub const CAMERA_SHAKE_MAX_ANGLE: f32 = 0.1;
+    /// trauma lost per second
+    pub const CAMERA_SHAKE_DECAY: f32 = 1.;
+    pub const CAMERA_SHAKE_NOISE_FREQUENCY: f32 = 15.;
+    /// per second, exponential
+    pub const CAMERA_KICK_DECAY: f32 = 12.;
+}
+
 pub mod character {
     // pub const CHAR_SCALE: f32 = 0.6 * super::TILE_SIZE;
     pub const CHAR_SCALE: f32 = 1. * super::TILE_SIZE;
@@ -11,11 +35,16 @@ pub mod character {
     pub const CHAR_HITBOX_WIDTH: f32 = 5. * CHAR_SCALE;
     pub const CHAR_HITBOX_Y_OFFSET: f32 = -6.25;
     pub const CHAR_SENSOR_Y_OFFSET: f32 = -1.25;
+    /// the feet in the 36 pixels high frames, from -0.5 (bottom) to 0.5 (top)
+    pub const CHAR_FEET_ANCHOR: f32 = CHAR_HITBOX_Y_OFFSET / 36.;
 
     pub const COLUMN_FRAME_IDLE_START: usize = 1;
     pub const COLUMN_FRAME_IDLE_END: usize = 6;
     pub const COLUMN_FRAME_RUN_START: usize = 7;
     pub const COLUMN_FRAME_RUN_END: usize = 14;
+    /// No dash frames in the spritesheet: the widest strides of the run.
+    pub const COLUMN_FRAME_DASH_START: usize = 9;
+    pub const COLUMN_FRAME_DASH_END: usize = 10;
     pub const COLUMN_FRAME_SHOOT_START: usize = 15;
     pub const COLUMN_FRAME_SHOOT_END: usize = 20;
     pub const COLUMN_FRAME_HIT_START: usize = 27;
@@ -29,11 +58,14 @@ pub mod character {
     pub const SPRITESHEET_COLUMN_NUMBER: usize = 47;
 
     pub mod player {
-        use crate::animations::sprite_sheet_animation::CharacterState;
+        use crate::animations::sprite_sheet_animation::{AnimationTag, CharacterState};
 
         use super::{
-            COLUMN_FRAME_IDLE_END, COLUMN_FRAME_IDLE_START, COLUMN_FRAME_RUN_START, COLUMN_FRAME_RUN_END,
-            SPRITESHEET_COLUMN_NUMBER,
+            COLUMN_FRAME_DASH_END, COLUMN_FRAME_DASH_START, COLUMN_FRAME_DEATH_END,
+            COLUMN_FRAME_DEATH_START, COLUMN_FRAME_HIT_END, COLUMN_FRAME_HIT_START,
+            COLUMN_FRAME_IDLE_END, COLUMN_FRAME_IDLE_START, COLUMN_FRAME_MELEE_END,
+            COLUMN_FRAME_MELEE_START, COLUMN_FRAME_RUN_END, COLUMN_FRAME_RUN_START,
+            COLUMN_FRAME_SHOOT_END, COLUMN_FRAME_SHOOT_START, SPRITESHEET_COLUMN_NUMBER,
         };
 
         pub const PLAYER_WIDTH: f32 = 12.;
@@ -43,6 +75,30 @@ pub mod character {
 
         pub const CAMERA_INTERPOLATION: f32 = 0.1;
 
+        /// With a gamepad, the reticle is at this distance from the player.
+        pub const RETICLE_DISTANCE: f32 = 30.;
+
+        pub const PLAYER_HEALTH: f32 = 10.;
+        /// in seconds, after being damaged
+        pub const PLAYER_INVULNERABILITY: f32 = 0.8;
+        /// Fraction of the max speed from which stopping skids.
+        pub const PLAYER_SKID_SPEED_RATIO: f32 = 0.9;
+
+        /* -------------------------------------------------------------------------- */
+        /*                                    Dash                                    */
+        /* -------------------------------------------------------------------------- */
+
+        pub const PLAYER_DASH_DISTANCE: f32 = 40.;
+        /// in seconds
+        pub const PLAYER_DASH_DURATION: f32 = 0.15;
+        /// in seconds
+        pub const PLAYER_DASH_COOLDOWN: f32 = 0.6;
+        /// in seconds, between two afterimages
+        pub const AFTERIMAGE_INTERVAL: f32 = 0.03;
+        /// in seconds
+        pub const AFTERIMAGE_LIFETIME: f32 = 0.25;
+        pub const AFTERIMAGE_ALPHA: f32 = 0.5;
+
         /* -------------------------------------------------------------------------- */
         /*                                  Animation                                 */
         /* -------------------------------------------------------------------------- */
@@ -56,17 +112,67 @@ pub mod character {
             // CharacterState::Idle,
             CharacterState::Run,
         );
+        // (frame, tag), from the first frame of the state
+        pub const PLAYER_RUN_NOTIFIES: &[(usize, AnimationTag)] =
+            &[(1, AnimationTag::Footstep), (5, AnimationTag::Footstep)];
         pub const PLAYER_IDLE_FRAMES: (usize, usize, CharacterState) = (
             PLAYER_LINE_START + COLUMN_FRAME_IDLE_START,
             PLAYER_LINE_START + COLUMN_FRAME_IDLE_END,
             CharacterState::Idle,
         );
+        pub const PLAYER_SHOOT_FRAMES: (usize, usize, CharacterState) = (
+            PLAYER_LINE_START + COLUMN_FRAME_SHOOT_START,
+            PLAYER_LINE_START + COLUMN_FRAME_SHOOT_END,
+            CharacterState::Idle,
+        );
+        pub const PLAYER_SHOOT_NOTIFIES: &[(usize, AnimationTag)] =
+            &[(0, AnimationTag::MuzzleFlash)];
+        pub const PLAYER_DASH_FRAMES: (usize, usize, CharacterState) = (
+            PLAYER_LINE_START + COLUMN_FRAME_DASH_START,
+            PLAYER_LINE_START + COLUMN_FRAME_DASH_END,
+            CharacterState::Dash,
+        );
+        pub const PLAYER_MELEE_FRAMES: (usize, usize, CharacterState) = (
+            PLAYER_LINE_START + COLUMN_FRAME_MELEE_START,
+            PLAYER_LINE_START + COLUMN_FRAME_MELEE_END,
+            CharacterState::Idle,
+        );
+        /// the hitbox is active on frames 2 and 3 of the swing
+        pub const PLAYER_MELEE_NOTIFIES: &[(usize, AnimationTag)] =
+            &[(2, AnimationTag::HitboxStart), (4, AnimationTag::HitboxEnd)];
+        /// half extents
+        pub const PLAYER_MELEE_HITBOX: (f32, f32) = (6., 6.);
+        /// from the character's center, facing right
+        pub const PLAYER_MELEE_HITBOX_OFFSET: (f32, f32) = (8., -1.);
+        /// target's velocity
+        pub const PLAYER_MELEE_KNOCKBACK: f32 = 120.;
+        pub const PLAYER_MELEE_DAMAGE: f32 = 3.;
+        pub const PLAYER_HIT_FRAMES: (usize, usize, CharacterState) = (
+            PLAYER_LINE_START + COLUMN_FRAME_HIT_START,
+            PLAYER_LINE_START + COLUMN_FRAME_HIT_END,
+            CharacterState::Idle,
+        );
+        /// holds its last frame
+        pub const PLAYER_DEATH_FRAMES: (usize, usize, CharacterState) = (
+            PLAYER_LINE_START + COLUMN_FRAME_DEATH_START,
+            PLAYER_LINE_START + COLUMN_FRAME_DEATH_END,
+            CharacterState::Death,
+        );
     }
 
     pub mod npcs {
+        use crate::constants::layers::CHARACTERS_Z;
 
         pub const NPC_SCALE: f32 = super::CHAR_SCALE;
 
+        /// half extents of the training dummy's hurtbox
+        pub const DUMMY_HURTBOX: (f32, f32) = (4., 7.);
+        pub const DUMMY_SPAWN: (f32, f32, f32) = (40., 0., CHARACTERS_Z);
+        pub const DUMMY_LINEAR_DAMPING: f32 = 8.;
+        pub const DUMMY_HEALTH: f32 = 30.;
+        /// in seconds without being damaged, before healing back to full health
+        pub const DUMMY_HEAL_DELAY: f32 = 3.;
+
         pub mod movement {
             use crate::constants::TILE_SIZE;
 
@@ -75,4 +181,184 @@ pub mod character {
     }
 }
 
+pub mod weapons {
+    pub const SHOTGUN_PELLET_COUNT: usize = 6;
+    /// in radian, between the two outer pellets
+    pub const SHOTGUN_SPREAD: f32 = 0.5;
+    pub const SHOTGUN_PELLET_SPEED: f32 = 400.;
+    /// shots per second
+    pub const SHOTGUN_FIRE_RATE: f32 = 1.5;
+    /// in seconds
+    pub const SHOTGUN_PELLET_LIFETIME: f32 = 0.35;
+    /// from the character's center, facing right
+    pub const SHOTGUN_MUZZLE_OFFSET: (f32, f32) = (10., -1.);
+
+    /// shooter's velocity, pushed back
+    pub const SHOTGUN_RECOIL: f32 = 80.;
+    pub const SHOTGUN_RECOIL_DECAY: f32 = 15.;
+    /// target's velocity, per pellet
+    pub const SHOTGUN_KNOCKBACK: f32 = 30.;
+    /// per pellet
+    pub const SHOTGUN_DAMAGE: f32 = 1.;
+    /// in seconds of flight: the pellets hitting sooner crit
+    pub const SHOTGUN_POINT_BLANK: f32 = 0.05;
+    pub const SHOTGUN_CRIT_MULTIPLIER: f32 = 2.;
+    /// camera's velocity, opposite to the shot
+    pub const SHOTGUN_CAMERA_KICK: f32 = 15.;
+
+    pub const PELLET_RADIUS: f32 = 0.5;
+
+    /// per second, exponential
+    pub const KNOCKBACK_DECAY: f32 = 10.;
+}
+
+pub mod effects {
+    use bevy::prelude::{Color, Vec2};
+
+    use crate::{animations::easing::Easing, effects::particles::ParticleEmitter};
+
+    /* -------------------------------------------------------------------------- */
+    /*                                   Flash                                    */
+    /* -------------------------------------------------------------------------- */
+
+    /// Saturates the texture to white.
+    pub const WHITE_FLASH: Color = Color::rgb(8., 8., 8.);
+    pub const RED_FLASH: Color = Color::rgb(4., 0.4, 0.4);
+    /// in seconds
+    pub const HIT_FLASH_DURATION: f32 = 0.12;
+
+    /* -------------------------------------------------------------------------- */
+    /*                               Damage Numbers                               */
+    /* -------------------------------------------------------------------------- */
+
+    pub const POPUP_POOL_SIZE: usize = 32;
+    /// in pixels, on screen
+    pub const POPUP_FONT_SIZE: f32 = 24.;
+    /// in seconds
+    pub const POPUP_LIFETIME: f32 = 0.7;
+    /// in world units, slowing down on the way
+    pub const POPUP_RISE: f32 = 6.;
+    /// Furthest sideways, either way.
+    pub const POPUP_DRIFT: f32 = 2.;
+    /// Scale reached at the top of the bounce.
+    pub const POPUP_BOUNCE: f32 = 1.6;
+    /// Fraction of the lifetime spent bouncing.
+    pub const POPUP_BOUNCE_TIME: f32 = 0.25;
+    /// Fraction of the lifetime before fading out.
+    pub const POPUP_FADE_START: f32 = 0.5;
+    /// Of a crit's number, relative to the others.
+    pub const POPUP_CRIT_SCALE: f32 = 1.5;
+
+    pub const POPUP_DAMAGE_COLOR: Color = Color::WHITE;
+    pub const POPUP_CRIT_COLOR: Color = Color::rgb(1., 0.8, 0.1);
+    pub const POPUP_HEAL_COLOR: Color = Color::rgb(0.3, 1., 0.4);
+
+    /* -------------------------------------------------------------------------- */
+    /*                              Squash & Stretch                              */
+    /* -------------------------------------------------------------------------- */
+
+    /// Positive squashes, negative stretches.
+    pub const RUN_START_SQUASH: f32 = -0.12;
+    pub const RUN_STOP_SQUASH: f32 = 0.15;
+    pub const DASH_SQUASH: f32 = 0.3;
+    /// A dash ends like a landing.
+    pub const DASH_END_SQUASH: f32 = 0.2;
+    pub const HIT_SQUASH: f32 = 0.25;
+    pub const SHOOT_SQUASH: f32 = 0.08;
+    /// The sprite never gets thinner or flatter than this.
+    pub const MAX_SQUASH: f32 = 0.5;
+    /// Pull of the spring towards the base shape.
+    pub const SQUASH_STIFFNESS: f32 = 400.;
+    pub const SQUASH_DAMPING: f32 = 14.;
+
+    /* -------------------------------------------------------------------------- */
+    /*                                 Particles                                  */
+    /* -------------------------------------------------------------------------- */
+
+    pub const PARTICLE_POOL_SIZE: usize = 128;
+
+    pub const FOOTSTEP_DUST: ParticleEmitter = ParticleEmitter {
+        count: (1, 2),
+        speed: (6., 14.),
+        spread: 0.6,
+        lifetime: (0.25, 0.4),
+        drag: 6.,
+        size: (2., 0.5),
+        size_curve: Easing::QuadraticIn,
+        color: (
+            Color::rgba(0.85, 0.8, 0.7, 0.8),
+            Color::rgba(0.85, 0.8, 0.7, 0.),
+        ),
+        radius: 1.,
+    };
+
+    pub const SKID_CLOUD: ParticleEmitter = ParticleEmitter {
+        count: (6, 9),
+        speed: (10., 35.),
+        spread: 1.2,
+        lifetime: (0.35, 0.6),
+        drag: 5.,
+        size: (2.5, 4.5),
+        size_curve: Easing::QuadraticOut,
+        color: (
+            Color::rgba(0.9, 0.85, 0.75, 0.9),
+            Color::rgba(0.9, 0.85, 0.75, 0.),
+        ),
+        radius: 2.,
+    };
+
+    /* -------------------------------------------------------------------------- */
+    /*                                   Muzzle                                   */
+    /* -------------------------------------------------------------------------- */
+
+    pub const MUZZLE_FLASH_COLOR: Color = Color::rgb(1., 0.9, 0.5);
+    pub const MUZZLE_FLASH_SIZE: Vec2 = Vec2::new(6., 4.);
+    /// in seconds
+    pub const MUZZLE_FLASH_DURATION: f32 = 0.08;
+
+    pub const MAX_CASINGS: usize = 24;
+    pub const CASING_COLOR: Color = Color::rgb(0.85, 0.6, 0.2);
+    pub const CASING_SIZE: Vec2 = Vec2::new(2., 1.);
+    /// Where the casing leaves the weapon, as a fraction of the muzzle offset.
+    pub const CASING_EJECTION_POINT: f32 = 0.3;
+    /// world units per second, backward
+    pub const CASING_EJECTION_SPEED: (f32, f32) = (15., 30.);
+    /// world units per second, up from the floor
+    pub const CASING_LIFT: (f32, f32) = (50., 70.);
+    /// radian per second
+    pub const CASING_SPIN: (f32, f32) = (15., 25.);
+    /// world units per second squared, pulling the fake height down
+    pub const CASING_GRAVITY: f32 = 400.;
+    /// Vertical speed kept by the bounce.
+    pub const CASING_RESTITUTION: f32 = 0.35;
+    /// Horizontal speed and spin kept by the bounce.
+    pub const CASING_FRICTION: f32 = 0.4;
+    /// in seconds, resting on the floor
+    pub const CASING_LINGER: f32 = 5.;
+    /// in seconds
+    pub const CASING_FADE_DURATION: f32 = 0.6;
+
+    /* -------------------------------------------------------------------------- */
+    /*                                   Decals                                   */
+    /* -------------------------------------------------------------------------- */
+
+    /// in world units
+    pub const DECAL_CELL_SIZE: f32 = 16.;
+    pub const MAX_DECALS_PER_CELL: usize = 12;
+    /// in seconds, before fading out
+    pub const DECAL_LIFETIME: f32 = 60.;
+    /// in seconds
+    pub const DECAL_FADE_DURATION: f32 = 1.5;
+
+    pub const BULLET_HOLE_COLOR: Color = Color::rgba(0.1, 0.08, 0.06, 0.9);
+    /// `(min, max)`, in world units
+    pub const BULLET_HOLE_SIZE: (f32, f32) = (0.8, 1.2);
+    pub const BLOOD_COLOR: Color = Color::rgba(0.5, 0.02, 0.03, 0.85);
+    /// `(min, max)`, in world units
+    pub const BLOOD_SIZE: (f32, f32) = (2., 3.5);
+    pub const SCORCH_COLOR: Color = Color::rgba(0.05, 0.04, 0.03, 0.6);
+    /// `(min, max)`, in world units
+    pub const SCORCH_SIZE: (f32, f32) = (6., 9.);
+}
+
 pub mod locations {}
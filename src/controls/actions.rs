@@ -0,0 +1,154 @@
+//! Actions
+//!
+//! Gameplay systems never read raw inputs: they ask the [`ActionState`]
+//! whether an [`Action`] is pressed, just pressed or just released,
+//! or how much it is (ie. how far the stick is pushed).
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::juice::JuiceEffect;
+
+use super::{
+    gamepad::{ActiveGamepad, GamepadDeadzones},
+    Binding, KeyBindings,
+};
+
+/// Above this value, an analog input counts as pressed.
+pub const PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Serialize, Deserialize,
+)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    AimUp,
+    AimDown,
+    AimLeft,
+    AimRight,
+    Interact,
+    Shoot,
+    Melee,
+    Dash,
+    /// Open/Close the rebinding menu.
+    Menu,
+    ToggleJuice(JuiceEffect),
+}
+
+/// The state of every [`Action`] this frame.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct ActionState {
+    /// In `[0; 1]`, only contains non-zero values.
+    values: HashMap<Action, f32>,
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    pub fn get_just_pressed(&self) -> impl Iterator<Item = Action> + '_ {
+        self.just_pressed.iter().copied()
+    }
+
+    /// `1.` for a pressed key, the stick's deflection for an analog input.
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.)
+    }
+
+    /// The non-zero value of every action.
+    pub fn values(&self) -> impl Iterator<Item = (Action, f32)> + '_ {
+        self.values.iter().map(|(action, value)| (*action, *value))
+    }
+
+    /// The movement direction, whose length is in `[0; 1]`.
+    ///
+    /// Proportional with a stick, normalized with digital inputs (ie. diagonals).
+    pub fn movement(&self) -> Vec2 {
+        Vec2::new(
+            self.value(Action::Right) - self.value(Action::Left),
+            self.value(Action::Up) - self.value(Action::Down),
+        )
+        .clamp_length_max(1.)
+    }
+
+    /// The aim direction, from an analog input (ie. the right stick).
+    ///
+    /// The mouse aims through the cursor, see [`Aim`](crate::combat::aim::Aim).
+    pub fn aim(&self) -> Vec2 {
+        Vec2::new(
+            self.value(Action::AimRight) - self.value(Action::AimLeft),
+            self.value(Action::AimUp) - self.value(Action::AimDown),
+        )
+        .clamp_length_max(1.)
+    }
+
+    /// Update the state from the value of each action this frame.
+    ///
+    /// An action is only *just* pressed/released when the first/last of its bindings is.
+    pub fn update(&mut self, values: HashMap<Action, f32>) {
+        let pressed = values
+            .iter()
+            .filter(|(_, value)| **value >= PRESS_THRESHOLD)
+            .map(|(action, _)| *action)
+            .collect::<HashSet<_>>();
+
+        self.just_pressed = pressed.difference(&self.pressed).copied().collect();
+        self.just_released = self.pressed.difference(&pressed).copied().collect();
+        self.pressed = pressed;
+        self.values = values;
+    }
+}
+
+pub fn update_action_state(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    active_gamepad: Res<ActiveGamepad>,
+    deadzones: Res<GamepadDeadzones>,
+    key_bindings: Res<KeyBindings>,
+    mut action_state: ResMut<ActionState>,
+) {
+    let binding_value = |binding: &Binding| match (binding, **active_gamepad) {
+        (Binding::Key(key), _) => keyboard_input.pressed(**key) as u8 as f32,
+        (Binding::MouseButton(mouse_button), _) => {
+            mouse_buttons.pressed(*mouse_button) as u8 as f32
+        }
+        (Binding::GamepadButton(button_type), Some(gamepad)) => {
+            gamepad_buttons.pressed(GamepadButton::new(gamepad, *button_type)) as u8 as f32
+        }
+        (Binding::GamepadAxis(axis_type, direction), Some(gamepad)) => {
+            (deadzones.axis_value(&gamepad_axes, gamepad, *axis_type) * direction.sign()).max(0.)
+        }
+        (_, None) => 0.,
+    };
+
+    let values = key_bindings
+        .iter()
+        .map(|(action, bindings)| {
+            let value = bindings.iter().map(binding_value).fold(0., f32::max);
+            (*action, value)
+        })
+        .filter(|(_, value)| *value > 0.)
+        .collect();
+
+    action_state.update(values);
+}
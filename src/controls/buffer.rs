@@ -0,0 +1,195 @@
+//! Input Buffer
+//!
+//! Juicy controls forgive slightly early inputs: a `just_pressed` action is remembered
+//! for a short window, so an attack pressed during another animation fires as soon as it
+//! becomes legal, through [`InputBuffer::consume_buffered`].
+//!
+//! Windows are measured on the [`GameTime`], so they don't expire during a hit-stop.
+//! Nothing is buffered outside of [`GameState::Playing`], where the [`GameTime`] is paused:
+//! the presses made in the rebinding menu would never expire.
+
+use bevy::{prelude::*, utils::HashMap};
+use std::time::Duration;
+
+use crate::{hit_stop::GameTime, GameState};
+
+use super::actions::{Action, ActionState};
+
+/// Default buffer window.
+pub const INPUT_BUFFER_DURATION: Duration = Duration::from_millis(120);
+
+#[derive(Debug, Resource)]
+pub struct InputBuffer {
+    /// When (in [`GameTime::elapsed`]) each buffered action was last pressed.
+    pressed_at: HashMap<Action, Duration>,
+    /// Per-action windows, [`InputBuffer::default_duration`] otherwise.
+    durations: HashMap<Action, Duration>,
+    pub default_duration: Duration,
+    now: Duration,
+}
+
+impl Default for InputBuffer {
+    fn default() -> Self {
+        InputBuffer {
+            pressed_at: HashMap::default(),
+            durations: HashMap::default(),
+            default_duration: INPUT_BUFFER_DURATION,
+            now: Duration::ZERO,
+        }
+    }
+}
+
+impl InputBuffer {
+    pub fn duration(&self, action: Action) -> Duration {
+        self.durations
+            .get(&action)
+            .copied()
+            .unwrap_or(self.default_duration)
+    }
+
+    pub fn set_duration(&mut self, action: Action, duration: Duration) {
+        self.durations.insert(action, duration);
+    }
+
+    /// Whether the action was pressed during its buffer window.
+    pub fn is_buffered(&self, action: Action) -> bool {
+        self.pressed_at.get(&action).map_or(false, |pressed_at| {
+            self.now.saturating_sub(*pressed_at) <= self.duration(action)
+        })
+    }
+
+    /// Returns `true`, only once, if the action was pressed during its buffer window.
+    ///
+    /// Call it only when the action can actually be performed.
+    pub fn consume_buffered(&mut self, action: Action) -> bool {
+        let buffered = self.is_buffered(action);
+        self.pressed_at.remove(&action);
+        buffered
+    }
+
+    /// Forget every buffered action.
+    pub fn clear(&mut self) {
+        self.pressed_at.clear();
+    }
+}
+
+pub fn buffer_actions(
+    game_time: Res<GameTime>,
+    action_state: Res<ActionState>,
+    mut input_buffer: ResMut<InputBuffer>,
+) {
+    let now = game_time.elapsed();
+    input_buffer.now = now;
+
+    for action in action_state.get_just_pressed() {
+        input_buffer.pressed_at.insert(action, now);
+    }
+
+    // forget the expired inputs
+    let InputBuffer {
+        pressed_at,
+        durations,
+        default_duration,
+        ..
+    } = &mut *input_buffer;
+    pressed_at.retain(|action, pressed_at| {
+        now.saturating_sub(*pressed_at) <= *durations.get(action).unwrap_or(default_duration)
+    });
+}
+
+/// The actions pressed before entering the menu are stale once back in game.
+pub fn clear_input_buffer(mut input_buffer: ResMut<InputBuffer>) {
+    input_buffer.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playing;
+
+    use super::*;
+
+    /// `action` pressed at one second, then `elapsed` later.
+    fn buffer_pressed(action: Action, elapsed: Duration) -> InputBuffer {
+        let pressed_at = Duration::from_secs(1);
+        let mut input_buffer = InputBuffer::default();
+        input_buffer.pressed_at.insert(action, pressed_at);
+        input_buffer.now = pressed_at + elapsed;
+        input_buffer
+    }
+
+    #[test]
+    fn press_is_consumed_inside_the_window() {
+        let mut input_buffer = buffer_pressed(Action::Melee, INPUT_BUFFER_DURATION / 2);
+
+        assert!(input_buffer.consume_buffered(Action::Melee));
+    }
+
+    #[test]
+    fn expired_press_is_not_consumed() {
+        let mut input_buffer = buffer_pressed(
+            Action::Melee,
+            INPUT_BUFFER_DURATION + Duration::from_millis(1),
+        );
+
+        assert!(!input_buffer.consume_buffered(Action::Melee));
+    }
+
+    #[test]
+    fn press_is_consumed_only_once() {
+        let mut input_buffer = buffer_pressed(Action::Melee, Duration::ZERO);
+
+        assert!(input_buffer.consume_buffered(Action::Melee));
+        assert!(!input_buffer.consume_buffered(Action::Melee));
+        assert!(!input_buffer.is_buffered(Action::Melee));
+    }
+
+    #[test]
+    fn per_action_window_overrides_the_default() {
+        let mut input_buffer = buffer_pressed(Action::Dash, INPUT_BUFFER_DURATION * 2);
+        input_buffer.set_duration(Action::Dash, INPUT_BUFFER_DURATION * 3);
+
+        assert!(input_buffer.consume_buffered(Action::Dash));
+    }
+
+    fn is_buffered(app: &App, action: Action) -> bool {
+        app.world.resource::<InputBuffer>().is_buffered(action)
+    }
+
+    #[test]
+    fn nothing_is_buffered_while_rebinding() {
+        let mut app = App::new();
+        app.add_state::<GameState>()
+            .init_resource::<GameTime>()
+            .init_resource::<ActionState>()
+            .init_resource::<InputBuffer>()
+            .add_systems(PreUpdate, buffer_actions.run_if(playing))
+            .add_systems(OnExit(GameState::Rebinding), clear_input_buffer);
+
+        // pressed just before opening the menu
+        app.world
+            .resource_mut::<ActionState>()
+            .update(HashMap::from_iter([(Action::Dash, 1.)]));
+        app.update();
+        assert!(is_buffered(&app, Action::Dash));
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Rebinding);
+        app.update();
+
+        // clicked or captured in the menu
+        app.world
+            .resource_mut::<ActionState>()
+            .update(HashMap::from_iter([(Action::Shoot, 1.)]));
+        app.update();
+        assert!(!is_buffered(&app, Action::Shoot));
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+
+        assert!(!is_buffered(&app, Action::Dash));
+        assert!(!is_buffered(&app, Action::Shoot));
+    }
+}
@@ -0,0 +1,258 @@
+//! Key Bindings Config
+//!
+//! [`KeyBindings`] are loaded from a RON file in the assets folder at startup,
+//! saved back whenever they change, and hot-reloaded when the file is edited.
+//!
+//! The current bindings (the defaults if the file is missing) are kept when the file is invalid.
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, FileAssetIo, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use super::{actions::Action, Binding, KeyBindings};
+
+/// Relative to the assets folder.
+pub const KEY_BINDINGS_PATH: &str = "config/key_bindings.keys.ron";
+
+#[derive(Debug, Clone, TypeUuid, TypePath)]
+#[uuid = "850562b7-d907-437b-b43c-1c73d8c661e0"]
+pub struct KeyBindingsConfig(pub KeyBindings);
+
+#[derive(Default)]
+pub struct KeyBindingsLoader;
+
+impl AssetLoader for KeyBindingsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let key_bindings = KeyBindings::from_ron(std::str::from_utf8(bytes)?)?;
+            load_context.set_default_asset(LoadedAsset::new(KeyBindingsConfig(key_bindings)));
+            Ok(())
+        })
+    }
+
+    /// Its own extension, not to claim every other RON asset.
+    fn extensions(&self) -> &[&str] {
+        &["keys.ron"]
+    }
+}
+
+#[derive(Debug)]
+pub enum KeyBindingsError {
+    /// One of the actions has a binding that can't be read.
+    InvalidEntry { entry: String, reason: String },
+    /// The file is not valid RON or not a map of actions (ie. an unknown action).
+    Invalid(ron::error::SpannedError),
+}
+
+impl fmt::Display for KeyBindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyBindingsError::InvalidEntry { entry, reason } => {
+                write!(f, "invalid key bindings entry `{entry}`: {reason}")
+            }
+            KeyBindingsError::Invalid(error) => write!(f, "invalid key bindings: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for KeyBindingsError {}
+
+impl KeyBindings {
+    /// Missing actions fall back to their default bindings.
+    pub fn from_ron(text: &str) -> Result<Self, KeyBindingsError> {
+        // the bindings are read entry by entry, to point at the faulty one
+        let entries = ron::from_str::<BTreeMap<Action, ron::Value>>(text)
+            .map_err(KeyBindingsError::Invalid)?;
+
+        let mut key_bindings = KeyBindings::default();
+        for (action, bindings) in entries {
+            let bindings = bindings.into_rust::<Vec<Binding>>().map_err(|reason| {
+                KeyBindingsError::InvalidEntry {
+                    entry: format!("{action:?}"),
+                    reason: reason.to_string(),
+                }
+            })?;
+            key_bindings.insert(action, bindings);
+        }
+        Ok(key_bindings)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+}
+
+/// The key bindings last read from or written to the config file.
+#[derive(Resource)]
+pub struct KeyBindingsFile {
+    /// Kept alive for the hot-reload.
+    handle: Handle<KeyBindingsConfig>,
+    synced: KeyBindings,
+}
+
+pub fn key_bindings_file_path() -> PathBuf {
+    FileAssetIo::get_base_path()
+        .join("assets")
+        .join(KEY_BINDINGS_PATH)
+}
+
+pub fn load_key_bindings(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut key_bindings: ResMut<KeyBindings>,
+) {
+    let path = key_bindings_file_path();
+
+    let synced = match fs::read_to_string(&path) {
+        Ok(text) => match KeyBindings::from_ron(&text) {
+            Ok(loaded_key_bindings) => {
+                *key_bindings = loaded_key_bindings.clone();
+                loaded_key_bindings
+            }
+            Err(error) => {
+                error!("{}: {error}", path.display());
+                // don't overwrite the user's file with the defaults
+                key_bindings.clone()
+            }
+        },
+        Err(_) => {
+            info!(
+                "No key bindings found at {}: using defaults",
+                path.display()
+            );
+            save_to_file(&key_bindings);
+            key_bindings.clone()
+        }
+    };
+
+    commands.insert_resource(KeyBindingsFile {
+        handle: asset_server.load(KEY_BINDINGS_PATH),
+        synced,
+    });
+}
+
+/// Apply the changes made to the config file.
+pub fn hot_reload_key_bindings(
+    mut asset_events: EventReader<AssetEvent<KeyBindingsConfig>>,
+    configs: Res<Assets<KeyBindingsConfig>>,
+    mut key_bindings_file: ResMut<KeyBindingsFile>,
+    mut key_bindings: ResMut<KeyBindings>,
+) {
+    for event in asset_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle }
+                if *handle == key_bindings_file.handle =>
+            {
+                if let Some(KeyBindingsConfig(loaded_key_bindings)) = configs.get(handle) {
+                    key_bindings_file.synced = loaded_key_bindings.clone();
+                    if *key_bindings != *loaded_key_bindings {
+                        info!("Key bindings reloaded");
+                        *key_bindings = loaded_key_bindings.clone();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Persist any change made in game.
+pub fn save_key_bindings(
+    mut key_bindings_file: ResMut<KeyBindingsFile>,
+    key_bindings: Res<KeyBindings>,
+) {
+    if key_bindings_file.synced != *key_bindings {
+        save_to_file(&key_bindings);
+        key_bindings_file.synced = key_bindings.clone();
+    }
+}
+
+fn save_to_file(key_bindings: &KeyBindings) {
+    let path = key_bindings_file_path();
+
+    if let Err(error) = write_key_bindings(key_bindings, &path) {
+        error!(
+            "Could not save the key bindings to {}: {error}",
+            path.display()
+        );
+    }
+}
+
+/// Creates the missing folders of `path`.
+fn write_key_bindings(
+    key_bindings: &KeyBindings,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let text = key_bindings.to_ron()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn key_bindings_file_round_trips() {
+        let folder = std::env::temp_dir().join(format!("key_bindings_test_{}", std::process::id()));
+        let path = folder.join(KEY_BINDINGS_PATH);
+        let mut key_bindings = KeyBindings::default();
+        key_bindings.insert(Action::Dash, vec![KeyCode::X.into()]);
+        write_key_bindings(&key_bindings, &path).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                asset_folder: folder.to_string_lossy().into_owned(),
+                ..default()
+            },
+        ))
+        .add_asset::<KeyBindingsConfig>()
+        .init_asset_loader::<KeyBindingsLoader>();
+        let handle = app
+            .world
+            .resource::<AssetServer>()
+            .load::<KeyBindingsConfig, _>(KEY_BINDINGS_PATH);
+
+        // loaded in the background
+        let mut loaded = None;
+        for _ in 0..200 {
+            app.update();
+            if let Some(KeyBindingsConfig(config)) = app
+                .world
+                .resource::<Assets<KeyBindingsConfig>>()
+                .get(&handle)
+            {
+                loaded = Some(config.clone());
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let loaded = loaded.expect("the key bindings file was not loaded");
+        assert_eq!(loaded, key_bindings);
+
+        let text = fs::read_to_string(&path).unwrap();
+        write_key_bindings(&loaded, &path).unwrap();
+        let saved_text = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(saved_text, text);
+    }
+}
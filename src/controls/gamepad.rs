@@ -0,0 +1,252 @@
+//! Gamepad
+//!
+//! Handles gamepads (hot-)plugging, stick deadzones and which device the player used last.
+//! Also names the mouse buttons, the other non-keyboard bindings.
+
+use bevy::{
+    input::gamepad::{GamepadConnectionEvent, GamepadEvent},
+    prelude::*,
+};
+
+/// The gamepad read by the [`ActionState`](super::actions::ActionState).
+#[derive(Debug, Default, Deref, DerefMut, Resource)]
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+/// Which device was used last, ie. to show the right prompts in the UI.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect, Resource)]
+#[reflect(Resource)]
+pub enum LastInputDevice {
+    /// Keyboard and mouse.
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+#[derive(Debug, Clone, Copy, Reflect, Resource)]
+#[reflect(Resource)]
+pub struct GamepadDeadzones {
+    /// Below this length, a stick is considered centered.
+    /// Above, its length is rescaled from `[radial; 1]` to `[0; 1]`.
+    pub radial: f32,
+    /// Below this value, a single axis is considered centered.
+    /// Helps to go perfectly straight with a stick.
+    pub axial: f32,
+}
+
+impl Default for GamepadDeadzones {
+    fn default() -> Self {
+        GamepadDeadzones {
+            radial: 0.15,
+            axial: 0.1,
+        }
+    }
+}
+
+impl GamepadDeadzones {
+    /// Apply the radial deadzone to a whole stick, then the axial one to each of its axes.
+    pub fn filter_stick(&self, stick: Vec2) -> Vec2 {
+        let length = stick.length();
+        if length <= self.radial {
+            return Vec2::ZERO;
+        }
+
+        let rescaled = ((length - self.radial) / (1. - self.radial)).min(1.);
+        let stick = stick / length * rescaled;
+
+        Vec2::new(self.filter_axis(stick.x), self.filter_axis(stick.y))
+    }
+
+    pub fn filter_axis(&self, value: f32) -> f32 {
+        if value.abs() <= self.axial {
+            0.
+        } else {
+            value
+        }
+    }
+
+    /// The value of an axis of the `gamepad`, deadzones applied.
+    pub fn axis_value(
+        &self,
+        axes: &Axis<GamepadAxis>,
+        gamepad: Gamepad,
+        axis_type: GamepadAxisType,
+    ) -> f32 {
+        let value = |axis_type| axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.);
+        let stick = |x_axis, y_axis| self.filter_stick(Vec2::new(value(x_axis), value(y_axis)));
+
+        match axis_type {
+            GamepadAxisType::LeftStickX => {
+                stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY).x
+            }
+            GamepadAxisType::LeftStickY => {
+                stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY).y
+            }
+            GamepadAxisType::RightStickX => {
+                stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY).x
+            }
+            GamepadAxisType::RightStickY => {
+                stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY).y
+            }
+            _ => self.filter_axis(value(axis_type)),
+        }
+    }
+}
+
+/// Keep an [`ActiveGamepad`] when gamepads are (un)plugged,
+/// and switch to the last one a button was pressed on.
+pub fn gamepad_connections(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut active_gamepad: ResMut<ActiveGamepad>,
+) {
+    for connection_event in connection_events.iter() {
+        let gamepad = connection_event.gamepad;
+
+        if connection_event.connected() {
+            info!("{gamepad:?} connected");
+            if active_gamepad.is_none() {
+                **active_gamepad = Some(gamepad);
+            }
+        } else {
+            info!("{gamepad:?} disconnected");
+            if **active_gamepad == Some(gamepad) {
+                **active_gamepad = gamepads.iter().find(|other| *other != gamepad);
+            }
+        }
+    }
+
+    if let Some(button) = gamepad_buttons.get_just_pressed().next() {
+        if **active_gamepad != Some(button.gamepad) {
+            **active_gamepad = Some(button.gamepad);
+        }
+    }
+}
+
+pub fn track_last_input_device(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    deadzones: Res<GamepadDeadzones>,
+    mut last_input_device: ResMut<LastInputDevice>,
+) {
+    let gamepad_used = gamepad_events.iter().any(|event| match event {
+        GamepadEvent::Button(button_event) => button_event.value > 0.,
+        GamepadEvent::Axis(axis_event) => deadzones.filter_axis(axis_event.value) != 0.,
+        GamepadEvent::Connection(_) => false,
+    });
+
+    let cursor_moved = cursor_moved_events.iter().last().is_some();
+
+    let device = if keyboard_input.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || cursor_moved
+    {
+        LastInputDevice::Keyboard
+    } else if gamepad_used {
+        LastInputDevice::Gamepad
+    } else {
+        return;
+    };
+
+    if *last_input_device != device {
+        *last_input_device = device;
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                    Names                                   */
+/* -------------------------------------------------------------------------- */
+
+pub fn button_name(button_type: GamepadButtonType) -> String {
+    match button_type {
+        GamepadButtonType::South => "South".to_string(),
+        GamepadButtonType::East => "East".to_string(),
+        GamepadButtonType::North => "North".to_string(),
+        GamepadButtonType::West => "West".to_string(),
+        GamepadButtonType::C => "C".to_string(),
+        GamepadButtonType::Z => "Z".to_string(),
+        GamepadButtonType::LeftTrigger => "LeftTrigger".to_string(),
+        GamepadButtonType::LeftTrigger2 => "LeftTrigger2".to_string(),
+        GamepadButtonType::RightTrigger => "RightTrigger".to_string(),
+        GamepadButtonType::RightTrigger2 => "RightTrigger2".to_string(),
+        GamepadButtonType::Select => "Select".to_string(),
+        GamepadButtonType::Start => "Start".to_string(),
+        GamepadButtonType::Mode => "Mode".to_string(),
+        GamepadButtonType::LeftThumb => "LeftThumb".to_string(),
+        GamepadButtonType::RightThumb => "RightThumb".to_string(),
+        GamepadButtonType::DPadUp => "DPadUp".to_string(),
+        GamepadButtonType::DPadDown => "DPadDown".to_string(),
+        GamepadButtonType::DPadLeft => "DPadLeft".to_string(),
+        GamepadButtonType::DPadRight => "DPadRight".to_string(),
+        GamepadButtonType::Other(id) => format!("Button{id}"),
+    }
+}
+
+pub fn button_from_name(name: &str) -> Option<GamepadButtonType> {
+    Some(match name {
+        "South" => GamepadButtonType::South,
+        "East" => GamepadButtonType::East,
+        "North" => GamepadButtonType::North,
+        "West" => GamepadButtonType::West,
+        "C" => GamepadButtonType::C,
+        "Z" => GamepadButtonType::Z,
+        "LeftTrigger" => GamepadButtonType::LeftTrigger,
+        "LeftTrigger2" => GamepadButtonType::LeftTrigger2,
+        "RightTrigger" => GamepadButtonType::RightTrigger,
+        "RightTrigger2" => GamepadButtonType::RightTrigger2,
+        "Select" => GamepadButtonType::Select,
+        "Start" => GamepadButtonType::Start,
+        "Mode" => GamepadButtonType::Mode,
+        "LeftThumb" => GamepadButtonType::LeftThumb,
+        "RightThumb" => GamepadButtonType::RightThumb,
+        "DPadUp" => GamepadButtonType::DPadUp,
+        "DPadDown" => GamepadButtonType::DPadDown,
+        "DPadLeft" => GamepadButtonType::DPadLeft,
+        "DPadRight" => GamepadButtonType::DPadRight,
+        other => GamepadButtonType::Other(other.strip_prefix("Button")?.parse().ok()?),
+    })
+}
+
+pub fn axis_name(axis_type: GamepadAxisType) -> String {
+    match axis_type {
+        GamepadAxisType::LeftStickX => "LeftStickX".to_string(),
+        GamepadAxisType::LeftStickY => "LeftStickY".to_string(),
+        GamepadAxisType::LeftZ => "LeftZ".to_string(),
+        GamepadAxisType::RightStickX => "RightStickX".to_string(),
+        GamepadAxisType::RightStickY => "RightStickY".to_string(),
+        GamepadAxisType::RightZ => "RightZ".to_string(),
+        GamepadAxisType::Other(id) => format!("Axis{id}"),
+    }
+}
+
+pub fn axis_from_name(name: &str) -> Option<GamepadAxisType> {
+    Some(match name {
+        "LeftStickX" => GamepadAxisType::LeftStickX,
+        "LeftStickY" => GamepadAxisType::LeftStickY,
+        "LeftZ" => GamepadAxisType::LeftZ,
+        "RightStickX" => GamepadAxisType::RightStickX,
+        "RightStickY" => GamepadAxisType::RightStickY,
+        "RightZ" => GamepadAxisType::RightZ,
+        other => GamepadAxisType::Other(other.strip_prefix("Axis")?.parse().ok()?),
+    })
+}
+
+pub fn mouse_button_name(mouse_button: MouseButton) -> String {
+    match mouse_button {
+        MouseButton::Left => "Left".to_string(),
+        MouseButton::Right => "Right".to_string(),
+        MouseButton::Middle => "Middle".to_string(),
+        MouseButton::Other(id) => format!("{id}"),
+    }
+}
+
+pub fn mouse_button_from_name(name: &str) -> Option<MouseButton> {
+    Some(match name {
+        "Left" => MouseButton::Left,
+        "Right" => MouseButton::Right,
+        "Middle" => MouseButton::Middle,
+        other => MouseButton::Other(other.parse().ok()?),
+    })
+}
@@ -0,0 +1,631 @@
+use bevy::{input::InputSystem, prelude::*};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    controls::{
+        actions::{update_action_state, Action, ActionState},
+        buffer::{buffer_actions, clear_input_buffer, InputBuffer},
+        config::{
+            hot_reload_key_bindings, load_key_bindings, save_key_bindings, KeyBindingsConfig,
+            KeyBindingsLoader,
+        },
+        gamepad::{
+            axis_from_name, axis_name, button_from_name, button_name, gamepad_connections,
+            mouse_button_from_name, mouse_button_name, track_last_input_device, ActiveGamepad,
+            GamepadDeadzones, LastInputDevice,
+        },
+    },
+    juice::JuiceEffect,
+    playing, GameState,
+};
+
+pub mod actions;
+pub mod buffer;
+pub mod config;
+pub mod gamepad;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<ActionState>()
+            .init_resource::<InputBuffer>()
+            .init_resource::<ActiveGamepad>()
+            .init_resource::<GamepadDeadzones>()
+            .init_resource::<LastInputDevice>()
+            .add_asset::<KeyBindingsConfig>()
+            .init_asset_loader::<KeyBindingsLoader>()
+            .add_systems(PreStartup, load_key_bindings)
+            .add_systems(OnExit(GameState::Rebinding), clear_input_buffer)
+            .add_systems(
+                PreUpdate,
+                (
+                    (
+                        gamepad_connections,
+                        update_action_state,
+                        buffer_actions.run_if(playing),
+                    )
+                        .chain(),
+                    track_last_input_device,
+                )
+                    .after(InputSystem),
+            )
+            .add_systems(
+                Update,
+                (
+                    hot_reload_key_bindings,
+                    save_key_bindings.run_if(resource_changed::<KeyBindings>()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Component, Debug, Deref, DerefMut, Clone, Copy, PartialEq, Eq)]
+pub struct Key(pub KeyCode);
+
+/// Generate the (stable) name of every [`KeyCode`].
+///
+/// The match in [`Key::name`] being exhaustive, a missing [`KeyCode`] won't compile.
+macro_rules! key_names {
+    ($($key_code:ident => $name:literal,)*) => {
+        impl Key {
+            /// Human-readable name, used in the config files.
+            pub fn name(&self) -> &'static str {
+                match self.0 {
+                    $(KeyCode::$key_code => $name,)*
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Key(KeyCode::$key_code)),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+key_names! {
+    Key1 => "Key1",
+    Key2 => "Key2",
+    Key3 => "Key3",
+    Key4 => "Key4",
+    Key5 => "Key5",
+    Key6 => "Key6",
+    Key7 => "Key7",
+    Key8 => "Key8",
+    Key9 => "Key9",
+    Key0 => "Key0",
+    A => "A",
+    B => "B",
+    C => "C",
+    D => "D",
+    E => "E",
+    F => "F",
+    G => "G",
+    H => "H",
+    I => "I",
+    J => "J",
+    K => "K",
+    L => "L",
+    M => "M",
+    N => "N",
+    O => "O",
+    P => "P",
+    Q => "Q",
+    R => "R",
+    S => "S",
+    T => "T",
+    U => "U",
+    V => "V",
+    W => "W",
+    X => "X",
+    Y => "Y",
+    Z => "Z",
+    Escape => "Escape",
+    F1 => "F1",
+    F2 => "F2",
+    F3 => "F3",
+    F4 => "F4",
+    F5 => "F5",
+    F6 => "F6",
+    F7 => "F7",
+    F8 => "F8",
+    F9 => "F9",
+    F10 => "F10",
+    F11 => "F11",
+    F12 => "F12",
+    F13 => "F13",
+    F14 => "F14",
+    F15 => "F15",
+    F16 => "F16",
+    F17 => "F17",
+    F18 => "F18",
+    F19 => "F19",
+    F20 => "F20",
+    F21 => "F21",
+    F22 => "F22",
+    F23 => "F23",
+    F24 => "F24",
+    Snapshot => "Snapshot",
+    Scroll => "Scroll",
+    Pause => "Pause",
+    Insert => "Insert",
+    Home => "Home",
+    Delete => "Delete",
+    End => "End",
+    PageDown => "PageDown",
+    PageUp => "PageUp",
+    Left => "ArrowLeft",
+    Up => "ArrowUp",
+    Right => "ArrowRight",
+    Down => "ArrowDown",
+    Back => "Back",
+    Return => "Return",
+    Space => "Space",
+    Compose => "Compose",
+    Caret => "Caret",
+    Numlock => "Numlock",
+    Numpad0 => "Numpad0",
+    Numpad1 => "Numpad1",
+    Numpad2 => "Numpad2",
+    Numpad3 => "Numpad3",
+    Numpad4 => "Numpad4",
+    Numpad5 => "Numpad5",
+    Numpad6 => "Numpad6",
+    Numpad7 => "Numpad7",
+    Numpad8 => "Numpad8",
+    Numpad9 => "Numpad9",
+    AbntC1 => "AbntC1",
+    AbntC2 => "AbntC2",
+    NumpadAdd => "NumpadAdd",
+    Apostrophe => "Apostrophe",
+    Apps => "Apps",
+    Asterisk => "Asterisk",
+    Plus => "Plus",
+    At => "At",
+    Ax => "Ax",
+    Backslash => "Backslash",
+    Calculator => "Calculator",
+    Capital => "Capital",
+    Colon => "Colon",
+    Comma => "Comma",
+    Convert => "Convert",
+    NumpadDecimal => "NumpadDecimal",
+    NumpadDivide => "NumpadDivide",
+    Equals => "Equals",
+    Grave => "Grave",
+    Kana => "Kana",
+    Kanji => "Kanji",
+    AltLeft => "AltLeft",
+    BracketLeft => "BracketLeft",
+    ControlLeft => "ControlLeft",
+    ShiftLeft => "ShiftLeft",
+    SuperLeft => "SuperLeft",
+    Mail => "Mail",
+    MediaSelect => "MediaSelect",
+    MediaStop => "MediaStop",
+    Minus => "Minus",
+    NumpadMultiply => "NumpadMultiply",
+    Mute => "Mute",
+    MyComputer => "MyComputer",
+    NavigateForward => "NavigateForward",
+    NavigateBackward => "NavigateBackward",
+    NextTrack => "NextTrack",
+    NoConvert => "NoConvert",
+    NumpadComma => "NumpadComma",
+    NumpadEnter => "NumpadEnter",
+    NumpadEquals => "NumpadEquals",
+    Oem102 => "Oem102",
+    Period => "Period",
+    PlayPause => "PlayPause",
+    Power => "Power",
+    PrevTrack => "PrevTrack",
+    AltRight => "AltRight",
+    BracketRight => "BracketRight",
+    ControlRight => "ControlRight",
+    ShiftRight => "ShiftRight",
+    SuperRight => "SuperRight",
+    Semicolon => "Semicolon",
+    Slash => "Slash",
+    Sleep => "Sleep",
+    Stop => "Stop",
+    NumpadSubtract => "NumpadSubtract",
+    Sysrq => "Sysrq",
+    Tab => "Tab",
+    Underline => "Underline",
+    Unlabeled => "Unlabeled",
+    VolumeDown => "VolumeDown",
+    VolumeUp => "VolumeUp",
+    Wake => "Wake",
+    WebBack => "WebBack",
+    WebFavorites => "WebFavorites",
+    WebForward => "WebForward",
+    WebHome => "WebHome",
+    WebRefresh => "WebRefresh",
+    WebSearch => "WebSearch",
+    WebStop => "WebStop",
+    Yen => "Yen",
+    Copy => "Copy",
+    Paste => "Paste",
+    Cut => "Cut",
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyNameVisitor;
+
+        impl<'de> Visitor<'de> for KeyNameVisitor {
+            type Value = Key;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a key name (ie. \"W\", \"ArrowUp\", \"Space\")")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Key::from_name(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_str(KeyNameVisitor)
+    }
+}
+
+/// Anything an [`Action`] can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(Key),
+    MouseButton(MouseButton),
+    GamepadButton(GamepadButtonType),
+    /// One direction of a gamepad axis (ie. the left stick pushed to the right).
+    GamepadAxis(GamepadAxisType, AxisDirection),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+impl AxisDirection {
+    pub fn sign(&self) -> f32 {
+        match self {
+            AxisDirection::Positive => 1.,
+            AxisDirection::Negative => -1.,
+        }
+    }
+}
+
+impl From<KeyCode> for Binding {
+    fn from(key_code: KeyCode) -> Self {
+        Binding::Key(Key(key_code))
+    }
+}
+
+impl From<MouseButton> for Binding {
+    fn from(mouse_button: MouseButton) -> Self {
+        Binding::MouseButton(mouse_button)
+    }
+}
+
+impl From<GamepadButtonType> for Binding {
+    fn from(button_type: GamepadButtonType) -> Self {
+        Binding::GamepadButton(button_type)
+    }
+}
+
+impl Binding {
+    /// Keys keep their own name, mouse buttons are prefixed by `Mouse`,
+    /// gamepad inputs are prefixed by `Gamepad`
+    /// and gamepad axes are suffixed by their direction (ie. `"GamepadLeftStickY+"`).
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => key.name().to_string(),
+            Binding::MouseButton(mouse_button) => {
+                format!("Mouse{}", mouse_button_name(*mouse_button))
+            }
+            Binding::GamepadButton(button_type) => format!("Gamepad{}", button_name(*button_type)),
+            Binding::GamepadAxis(axis_type, direction) => format!(
+                "Gamepad{}{}",
+                axis_name(*axis_type),
+                match direction {
+                    AxisDirection::Positive => '+',
+                    AxisDirection::Negative => '-',
+                }
+            ),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(mouse_name) = name.strip_prefix("Mouse") {
+            return mouse_button_from_name(mouse_name).map(Binding::MouseButton);
+        }
+        let Some(gamepad_name) = name.strip_prefix("Gamepad") else {
+            return Key::from_name(name).map(Binding::Key);
+        };
+
+        if let Some(axis) = gamepad_name.strip_suffix('+') {
+            axis_from_name(axis)
+                .map(|axis_type| Binding::GamepadAxis(axis_type, AxisDirection::Positive))
+        } else if let Some(axis) = gamepad_name.strip_suffix('-') {
+            axis_from_name(axis)
+                .map(|axis_type| Binding::GamepadAxis(axis_type, AxisDirection::Negative))
+        } else {
+            button_from_name(gamepad_name).map(Binding::GamepadButton)
+        }
+    }
+}
+
+impl Serialize for Binding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BindingNameVisitor;
+
+        impl<'de> Visitor<'de> for BindingNameVisitor {
+            type Value = Binding;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "a key, mouse or gamepad input name (ie. \"W\", \"MouseLeft\", \"GamepadLeftStickX+\")",
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Binding::from_name(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_str(BindingNameVisitor)
+    }
+}
+
+/// Each [`Action`] can be bound to any number of keys, mouse buttons and gamepad inputs.
+#[derive(Serialize, Deserialize, Deref, DerefMut, Clone, PartialEq, Eq, Debug, Resource)]
+#[serde(transparent)]
+pub struct KeyBindings(pub BTreeMap<Action, Vec<Binding>>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use AxisDirection::{Negative, Positive};
+        use GamepadAxisType::{LeftStickX, LeftStickY, RightStickX, RightStickY};
+
+        let mut key_bindings = BTreeMap::from([
+            (
+                Action::Up,
+                vec![
+                    KeyCode::W.into(),
+                    KeyCode::Z.into(),
+                    KeyCode::Up.into(),
+                    GamepadButtonType::DPadUp.into(),
+                    Binding::GamepadAxis(LeftStickY, Positive),
+                ],
+            ),
+            (
+                Action::Down,
+                vec![
+                    KeyCode::S.into(),
+                    KeyCode::Down.into(),
+                    GamepadButtonType::DPadDown.into(),
+                    Binding::GamepadAxis(LeftStickY, Negative),
+                ],
+            ),
+            (
+                Action::Left,
+                vec![
+                    KeyCode::A.into(),
+                    KeyCode::Q.into(),
+                    KeyCode::Left.into(),
+                    GamepadButtonType::DPadLeft.into(),
+                    Binding::GamepadAxis(LeftStickX, Negative),
+                ],
+            ),
+            (
+                Action::Right,
+                vec![
+                    KeyCode::D.into(),
+                    KeyCode::Right.into(),
+                    GamepadButtonType::DPadRight.into(),
+                    Binding::GamepadAxis(LeftStickX, Positive),
+                ],
+            ),
+            (
+                Action::AimUp,
+                vec![Binding::GamepadAxis(RightStickY, Positive)],
+            ),
+            (
+                Action::AimDown,
+                vec![Binding::GamepadAxis(RightStickY, Negative)],
+            ),
+            (
+                Action::AimLeft,
+                vec![Binding::GamepadAxis(RightStickX, Negative)],
+            ),
+            (
+                Action::AimRight,
+                vec![Binding::GamepadAxis(RightStickX, Positive)],
+            ),
+            (
+                Action::Interact,
+                vec![
+                    KeyCode::E.into(),
+                    KeyCode::R.into(),
+                    GamepadButtonType::West.into(),
+                ],
+            ),
+            (
+                Action::Shoot,
+                vec![
+                    MouseButton::Left.into(),
+                    KeyCode::J.into(),
+                    GamepadButtonType::RightTrigger2.into(),
+                ],
+            ),
+            (
+                Action::Melee,
+                vec![
+                    MouseButton::Right.into(),
+                    KeyCode::K.into(),
+                    GamepadButtonType::East.into(),
+                ],
+            ),
+            (
+                Action::Dash,
+                vec![
+                    KeyCode::Space.into(),
+                    KeyCode::ShiftLeft.into(),
+                    GamepadButtonType::South.into(),
+                ],
+            ),
+            (
+                Action::Menu,
+                vec![KeyCode::Escape.into(), GamepadButtonType::Start.into()],
+            ),
+        ]);
+
+        let number_keys = [
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+            KeyCode::Key6,
+            KeyCode::Key7,
+        ];
+        for (effect, key_code) in JuiceEffect::ALL.iter().zip(number_keys) {
+            key_bindings.insert(Action::ToggleJuice(*effect), vec![key_code.into()]);
+        }
+
+        KeyBindings(key_bindings)
+    }
+}
+
+impl KeyBindings {
+    /// Every action using this binding.
+    /// More than one means a conflict.
+    pub fn actions_bound_to(&self, binding: Binding) -> Vec<Action> {
+        self.iter()
+            .filter(|(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// The binding to show in a prompt for this action, ie. `[E] Interact`.
+    pub fn prompt(&self, action: Action, device: LastInputDevice) -> Option<Binding> {
+        self.get(&action)?
+            .iter()
+            .find(|binding| match device {
+                LastInputDevice::Keyboard => {
+                    matches!(binding, Binding::Key(_) | Binding::MouseButton(_))
+                }
+                LastInputDevice::Gamepad => {
+                    !matches!(binding, Binding::Key(_) | Binding::MouseButton(_))
+                }
+            })
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::{DynamicEnum, DynamicVariant, FromReflect, TypeInfo, Typed};
+
+    use super::{config::KeyBindingsError, *};
+
+    /// Every [`KeyCode`] variant, listed through reflection.
+    fn all_key_codes() -> Vec<KeyCode> {
+        let TypeInfo::Enum(enum_info) = KeyCode::type_info() else {
+            panic!("KeyCode is not an enum");
+        };
+
+        enum_info
+            .variant_names()
+            .iter()
+            .map(|name| {
+                KeyCode::from_reflect(&DynamicEnum::new(*name, DynamicVariant::Unit))
+                    .unwrap_or_else(|| panic!("KeyCode::{name} is not a unit variant"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_key_round_trips() {
+        for key_code in all_key_codes() {
+            let key = Key(key_code);
+            let serialized = ron::to_string(&key).unwrap();
+
+            assert_eq!(serialized, format!("\"{}\"", key.name()));
+            assert_eq!(ron::from_str::<Key>(&serialized).unwrap(), key);
+        }
+    }
+
+    #[test]
+    fn key_names_are_unique() {
+        let mut names = all_key_codes()
+            .into_iter()
+            .map(|key_code| Key(key_code).name())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+
+        assert_eq!(names.len(), all_key_codes().len());
+    }
+
+    #[test]
+    fn unknown_key_name_is_an_error() {
+        assert!(ron::from_str::<Key>("\"NotAKey\"").is_err());
+        assert!(ron::from_str::<Key>("42").is_err());
+    }
+
+    #[test]
+    fn key_bindings_round_trip() {
+        let key_bindings = KeyBindings::default();
+        let text = key_bindings.to_ron().unwrap();
+
+        assert_eq!(KeyBindings::from_ron(&text).unwrap(), key_bindings);
+    }
+
+    #[test]
+    fn missing_actions_fall_back_to_defaults() {
+        let key_bindings = KeyBindings::from_ron(r#"{ Up: ["ArrowUp"] }"#).unwrap();
+
+        assert_eq!(key_bindings[&Action::Up], vec![KeyCode::Up.into()]);
+        assert_eq!(
+            key_bindings[&Action::Down],
+            KeyBindings::default()[&Action::Down]
+        );
+    }
+
+    #[test]
+    fn invalid_entry_is_reported() {
+        let error = KeyBindings::from_ron(
+            "{\n    Up: [\"W\"],\n    Down: [\"S\", \"NotAKey\"],\n    Dash: [\"Space\"],\n}",
+        )
+        .unwrap_err();
+
+        match error {
+            KeyBindingsError::InvalidEntry { entry, reason } => {
+                assert_eq!(entry, "Down");
+                assert!(reason.contains("NotAKey"), "{reason}");
+            }
+            error => panic!("expected an invalid entry, got {error}"),
+        }
+    }
+}
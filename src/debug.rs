@@ -1,11 +1,35 @@
 use bevy::prelude::*;
-use bevy_inspector_egui::quick::{StateInspectorPlugin, WorldInspectorPlugin};
+use bevy_inspector_egui::quick::{
+    ResourceInspectorPlugin, StateInspectorPlugin, WorldInspectorPlugin,
+};
 
 use crate::{
-    animations::sprite_sheet_animation::{
-        AnimationIndices, CharacterState, SpriteSheetAnimation, TempoAnimation,
+    animations::{
+        easing::Easing,
+        sprite_sheet_animation::{
+            AnimationIndices, AnimationNotifies, AnimationTag, CharacterState,
+            SpriteSheetAnimation, TempoAnimation,
+        },
     },
+    camera::CameraShake,
+    characters::dash::Dash,
     collisions::{TesselatedCollider, TesselatedColliderConfig},
+    combat::{
+        aim::Aim,
+        health::{Health, Invulnerable},
+        knockback::KnockbackVelocity,
+        melee::MeleeAttack,
+        shoot::Weapon,
+    },
+    controls::gamepad::{GamepadDeadzones, LastInputDevice},
+    effects::{
+        decals::Decal,
+        flash::{Flash, HitFlash},
+        squash::SquashStretch,
+    },
+    hit_stop::{Frozen, GameTime},
+    juice::{JuiceSettings, JuiceToggle},
+    movement::{MovementFeel, Speed},
     GameState,
 };
 
@@ -18,16 +42,59 @@ impl Plugin for DebugPlugin {
                 .register_type::<GameState>()
                 .add_plugins((StateInspectorPlugin::<GameState>::default(),))
                 /* -------------------------------------------------------------------------- */
+                /*                                    Juice                                   */
+                /* -------------------------------------------------------------------------- */
+                .register_type::<JuiceToggle>()
+                .register_type::<JuiceSettings>()
+                .add_plugins((ResourceInspectorPlugin::<JuiceSettings>::default(),))
+                /* -------------------------------------------------------------------------- */
+                /*                                  Controls                                  */
+                /* -------------------------------------------------------------------------- */
+                .register_type::<GamepadDeadzones>()
+                .register_type::<LastInputDevice>()
+                .add_plugins((
+                    ResourceInspectorPlugin::<GamepadDeadzones>::default(),
+                    ResourceInspectorPlugin::<LastInputDevice>::default(),
+                ))
+                /* -------------------------------------------------------------------------- */
                 /*                              Global Animation                              */
                 /* -------------------------------------------------------------------------- */
                 .register_type::<TempoAnimation>()
                 .register_type::<SpriteSheetAnimation>()
+                .register_type::<GameTime>()
+                .register_type::<Frozen>()
+                /* -------------------------------------------------------------------------- */
+                /*                                   Camera                                   */
+                /* -------------------------------------------------------------------------- */
+                .register_type::<CameraShake>()
                 /* -------------------------------------------------------------------------- */
                 /*                                  Character                                 */
                 /* -------------------------------------------------------------------------- */
                 .register_type::<AnimationIndices>()
+                .register_type::<AnimationNotifies>()
+                .register_type::<AnimationTag>()
                 .register_type::<CharacterState>()
                 /* -------------------------------------------------------------------------- */
+                /*                                  Movement                                  */
+                /* -------------------------------------------------------------------------- */
+                .register_type::<Speed>()
+                .register_type::<MovementFeel>()
+                .register_type::<Easing>()
+                .register_type::<Dash>()
+                /* -------------------------------------------------------------------------- */
+                /*                                   Combat                                   */
+                /* -------------------------------------------------------------------------- */
+                .register_type::<Weapon>()
+                .register_type::<MeleeAttack>()
+                .register_type::<Aim>()
+                .register_type::<KnockbackVelocity>()
+                .register_type::<Health>()
+                .register_type::<Invulnerable>()
+                .register_type::<Flash>()
+                .register_type::<HitFlash>()
+                .register_type::<SquashStretch>()
+                .register_type::<Decal>()
+                /* -------------------------------------------------------------------------- */
                 /*                                   Hitbox                                   */
                 /* -------------------------------------------------------------------------- */
                 .register_type::<TesselatedCollider>()
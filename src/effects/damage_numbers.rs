@@ -0,0 +1,280 @@
+//! Damage Numbers
+//!
+//! A [`PopupNumber`] pops up where a hit landed, drifts, bounces in scale and fades out:
+//! three tweens played together by an [`Animator`].
+//! Every [`Damaged`] shows one: the pellets of a blast hitting the same target
+//! the same frame add up into a single number, a crit if any of them was.
+//! Every [`Healed`] shows one as well.
+//!
+//! Popups are text entities of a [`Pool`], hidden when not shown.
+//!
+//! They live in world space but keep the same size on screen:
+//! their scale follows the [`PlayerCamera`]'s projection when they pop.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    animations::{
+        easing::Easing,
+        tween::{Animator, OnCompleted, Tween, TweenCompleted, TweenSet, TweenTarget, Tweenable},
+    },
+    combat::health::{apply_heal, Damaged, Healed},
+    constants::{
+        effects::{
+            POPUP_BOUNCE, POPUP_BOUNCE_TIME, POPUP_CRIT_COLOR, POPUP_CRIT_SCALE,
+            POPUP_DAMAGE_COLOR, POPUP_DRIFT, POPUP_FADE_START, POPUP_FONT_SIZE, POPUP_HEAL_COLOR,
+            POPUP_LIFETIME, POPUP_POOL_SIZE, POPUP_RISE,
+        },
+        layers::POPUPS_Z,
+    },
+    effects::Pool,
+    PlayerCamera,
+};
+
+/// Tells apart the popups' [`TweenCompleted`].
+const POPUP_TWEEN_TAG: u64 = u64::from_be_bytes(*b"popupnum");
+
+pub struct DamageNumbersPlugin;
+
+impl Plugin for DamageNumbersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pool<PopupNumber>>()
+            .add_event::<ShowPopup>()
+            .add_systems(Startup, spawn_popup_pool)
+            .add_systems(
+                PostUpdate,
+                (
+                    (popup_on_damage, popup_on_heal, show_popups)
+                        .chain()
+                        .after(apply_heal)
+                        .before(TweenSet),
+                    hide_finished_popups.after(TweenSet),
+                ),
+            );
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum PopupKind {
+    #[default]
+    Damage,
+    Crit,
+    Heal,
+}
+
+impl PopupKind {
+    pub fn color(self) -> Color {
+        match self {
+            PopupKind::Damage => POPUP_DAMAGE_COLOR,
+            PopupKind::Crit => POPUP_CRIT_COLOR,
+            PopupKind::Heal => POPUP_HEAL_COLOR,
+        }
+    }
+
+    /// Crits pop bigger.
+    pub fn scale(self) -> f32 {
+        match self {
+            PopupKind::Crit => POPUP_CRIT_SCALE,
+            PopupKind::Damage | PopupKind::Heal => 1.,
+        }
+    }
+}
+
+/// Show `amount` at `position`, in world space.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct ShowPopup {
+    pub position: Vec2,
+    pub amount: f32,
+    pub kind: PopupKind,
+}
+
+/// A pooled number, hidden when not shown.
+#[derive(Debug, Default, Component)]
+pub struct PopupNumber;
+
+fn spawn_popup_pool(mut commands: Commands, mut pool: ResMut<Pool<PopupNumber>>) {
+    for _ in 0..POPUP_POOL_SIZE {
+        let popup = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: POPUP_FONT_SIZE,
+                            color: POPUP_DAMAGE_COLOR,
+                            ..default()
+                        },
+                    ),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                PopupNumber,
+                Name::new("Popup Number"),
+            ))
+            .id();
+        pool.insert(popup);
+    }
+}
+
+fn popup_on_damage(
+    mut damaged_events: EventReader<Damaged>,
+    mut popup_events: EventWriter<ShowPopup>,
+    transform_query: Query<&GlobalTransform>,
+) {
+    // (position, amount, crit) per target
+    let mut popups = HashMap::<Entity, (Vec2, f32, bool)>::new();
+
+    for damaged in damaged_events.iter() {
+        let Some(position) = damaged.position.or_else(|| {
+            transform_query
+                .get(damaged.target)
+                .ok()
+                .map(|transform| transform.translation().truncate())
+        }) else {
+            continue;
+        };
+
+        // the first hit places the number
+        let (_, amount, crit) = popups
+            .entry(damaged.target)
+            .or_insert((position, 0., false));
+        *amount += damaged.amount;
+        *crit |= damaged.crit;
+    }
+
+    for (position, amount, crit) in popups.into_values() {
+        popup_events.send(ShowPopup {
+            position,
+            amount,
+            kind: if crit {
+                PopupKind::Crit
+            } else {
+                PopupKind::Damage
+            },
+        });
+    }
+}
+
+fn popup_on_heal(
+    mut healed_events: EventReader<Healed>,
+    mut popup_events: EventWriter<ShowPopup>,
+    transform_query: Query<&GlobalTransform>,
+) {
+    for healed in healed_events.iter() {
+        let Ok(transform) = transform_query.get(healed.target) else {
+            continue;
+        };
+        popup_events.send(ShowPopup {
+            position: transform.translation().truncate(),
+            amount: healed.amount,
+            kind: PopupKind::Heal,
+        });
+    }
+}
+
+fn show_popups(
+    mut commands: Commands,
+    mut popup_events: EventReader<ShowPopup>,
+    mut pool: ResMut<Pool<PopupNumber>>,
+    // popups ever shown, to vary their drift
+    mut popup_count: Local<u32>,
+    camera_query: Query<&OrthographicProjection, With<PlayerCamera>>,
+    mut popup_query: Query<(&mut Text, &mut Transform, &mut Visibility), With<PopupNumber>>,
+) {
+    // one world unit is `1 / scale` pixels
+    let camera_scale = camera_query
+        .get_single()
+        .map_or(1., |projection| projection.scale);
+
+    for ShowPopup {
+        position,
+        amount,
+        kind,
+    } in popup_events.iter()
+    {
+        // an empty pool, but the next events are still read
+        let Some(popup) = pool.acquire() else {
+            continue;
+        };
+        *popup_count = popup_count.wrapping_add(1);
+        let Ok((mut text, mut transform, mut visibility)) = popup_query.get_mut(popup) else {
+            continue;
+        };
+
+        let section = &mut text.sections[0];
+        section.value = match kind {
+            PopupKind::Heal => format!("+{}", amount.round()),
+            PopupKind::Damage | PopupKind::Crit => format!("{}", amount.round()),
+        };
+        section.style.color = kind.color();
+
+        let start = position.extend(POPUPS_Z);
+        // spread consecutive popups left and right
+        let drift = ((*popup_count as f32 * 0.618).fract() * 2. - 1.) * POPUP_DRIFT;
+        let end = start + Vec3::new(drift, POPUP_RISE, 0.);
+        let scale = Vec3::splat(camera_scale * kind.scale());
+        let transparent = *kind.color().set_a(0.);
+
+        transform.translation = start;
+        transform.scale = Vec3::ZERO;
+        *visibility = Visibility::Inherited;
+
+        let rise = Tween::new(TweenTarget::Translation { start, end }, POPUP_LIFETIME)
+            .with_easing(Easing::ExponentialOut);
+        // overshoot then settle
+        let bounce_time = POPUP_LIFETIME * POPUP_BOUNCE_TIME / 2.;
+        let bounce = Tweenable::from(
+            Tween::new(
+                TweenTarget::Scale {
+                    start: Vec3::ZERO,
+                    end: scale * POPUP_BOUNCE,
+                },
+                bounce_time,
+            )
+            .with_easing(Easing::QuadraticOut),
+        )
+        .then(
+            Tween::new(
+                TweenTarget::Scale {
+                    start: scale * POPUP_BOUNCE,
+                    end: scale,
+                },
+                bounce_time,
+            )
+            .with_easing(Easing::SmoothStep),
+        );
+        let fade = Tweenable::Delay(POPUP_LIFETIME * POPUP_FADE_START).then(
+            Tween::new(
+                TweenTarget::TextColor {
+                    start: kind.color(),
+                    end: transparent,
+                },
+                POPUP_LIFETIME * (1. - POPUP_FADE_START),
+            )
+            .with_easing(Easing::QuadraticIn),
+        );
+
+        // replaces the animator of a recycled popup
+        commands.entity(popup).insert(
+            Animator::new(Tweenable::from(rise).with(bounce).with(fade))
+                .with_tag(POPUP_TWEEN_TAG)
+                .on_completed(OnCompleted::Remove),
+        );
+    }
+}
+
+fn hide_finished_popups(
+    mut completed_events: EventReader<TweenCompleted>,
+    mut pool: ResMut<Pool<PopupNumber>>,
+    mut popup_query: Query<&mut Visibility, With<PopupNumber>>,
+) {
+    for completed in completed_events.iter() {
+        if completed.tag != POPUP_TWEEN_TAG {
+            continue;
+        }
+        if let Ok(mut visibility) = popup_query.get_mut(completed.entity) {
+            *visibility = Visibility::Hidden;
+            pool.release(completed.entity);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! Decals
+//!
+//! Marks left on the map, through a [`SpawnDecal`]: bullet holes where pellets hit the walls,
+//! blood where characters are damaged and scorch marks under the muzzle of each blast.
+//! They are children of the decal layer, just above the map background and below the characters.
+//!
+//! Decals fade out after [`DECAL_LIFETIME`].
+//! The map is also divided in square cells of [`DECAL_CELL_SIZE`]:
+//! past [`MAX_DECALS_PER_CELL`] in a cell, its oldest decal fades out sooner.
+
+use std::{collections::VecDeque, f32::consts::TAU};
+
+use bevy::{prelude::*, transform::TransformSystem, utils::HashMap};
+use rand::Rng;
+
+use crate::{
+    animations::{
+        sprite_sheet_animation::{jump_frame_character_state, AnimationEvent, AnimationTag},
+        tween::{Animator, OnCompleted, Tween, TweenSet, TweenTarget},
+    },
+    combat::{
+        health::{apply_damage, Damaged},
+        shoot::{WallHit, Weapon},
+    },
+    constants::{
+        character::CHAR_HITBOX_Y_OFFSET,
+        effects::{
+            BLOOD_COLOR, BLOOD_SIZE, BULLET_HOLE_COLOR, BULLET_HOLE_SIZE, DECAL_CELL_SIZE,
+            DECAL_FADE_DURATION, DECAL_LIFETIME, MAX_DECALS_PER_CELL, SCORCH_COLOR, SCORCH_SIZE,
+        },
+        layers::DECALS_Z,
+    },
+    effects::muzzle::barrel,
+    hit_stop::GameTime,
+    juice::{juice_enabled, JuiceEffect},
+    replay::EffectsRng,
+};
+
+pub struct DecalsPlugin;
+
+impl Plugin for DecalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DecalGrid>()
+            .add_event::<SpawnDecal>()
+            .add_systems(Startup, spawn_decal_layer)
+            .add_systems(
+                PostUpdate,
+                (
+                    (
+                        decals_on_wall_hit,
+                        decals_on_damage.after(apply_damage),
+                        decals_on_blast.after(jump_frame_character_state),
+                    )
+                        .run_if(juice_enabled(JuiceEffect::Particles)),
+                    spawn_decals,
+                    // always, to clear the map when the juice is toggled off
+                    fade_old_decals,
+                )
+                    .chain()
+                    .before(TweenSet)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum DecalKind {
+    #[default]
+    BulletHole,
+    Blood,
+    Scorch,
+}
+
+impl DecalKind {
+    pub fn color(self) -> Color {
+        match self {
+            DecalKind::BulletHole => BULLET_HOLE_COLOR,
+            DecalKind::Blood => BLOOD_COLOR,
+            DecalKind::Scorch => SCORCH_COLOR,
+        }
+    }
+
+    /// `(min, max)`, in world units
+    fn size(self) -> (f32, f32) {
+        match self {
+            DecalKind::BulletHole => BULLET_HOLE_SIZE,
+            DecalKind::Blood => BLOOD_SIZE,
+            DecalKind::Scorch => SCORCH_SIZE,
+        }
+    }
+}
+
+/// Leave a `kind` mark at `position`, in world space.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct SpawnDecal {
+    pub position: Vec2,
+    pub kind: DecalKind,
+}
+
+#[derive(Debug, Default, Reflect, Component)]
+#[reflect(Component)]
+pub struct Decal {
+    pub kind: DecalKind,
+    /// Fades out when finished.
+    pub lifetime: Timer,
+}
+
+/// The parent of every decal.
+#[derive(Debug, Component)]
+pub struct DecalLayer;
+
+/// The decals of each cell of the map, oldest first.
+#[derive(Debug, Default, Resource)]
+pub struct DecalGrid {
+    cells: HashMap<IVec2, VecDeque<Entity>>,
+}
+
+impl DecalGrid {
+    fn cell(position: Vec2) -> IVec2 {
+        (position / DECAL_CELL_SIZE).floor().as_ivec2()
+    }
+
+    /// Returns the decals pushed out of the cell.
+    fn insert(&mut self, position: Vec2, decal: Entity) -> Vec<Entity> {
+        let cell = self.cells.entry(Self::cell(position)).or_default();
+        cell.push_back(decal);
+        let excess = cell.len().saturating_sub(MAX_DECALS_PER_CELL);
+        cell.drain(..excess).collect()
+    }
+
+    /// Returns `false` if the decal was already pushed out of its cell.
+    fn remove(&mut self, position: Vec2, decal: Entity) -> bool {
+        let Some(cell) = self.cells.get_mut(&Self::cell(position)) else {
+            return false;
+        };
+        let Some(index) = cell.iter().position(|entity| *entity == decal) else {
+            return false;
+        };
+        cell.remove(index);
+        if cell.is_empty() {
+            self.cells.remove(&Self::cell(position));
+        }
+        true
+    }
+}
+
+fn spawn_decal_layer(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_xyz(0., 0., DECALS_Z)),
+        DecalLayer,
+        Name::new("Map - Decals"),
+    ));
+}
+
+fn decals_on_wall_hit(
+    mut wall_hit_events: EventReader<WallHit>,
+    mut decal_events: EventWriter<SpawnDecal>,
+) {
+    for wall_hit in wall_hit_events.iter() {
+        decal_events.send(SpawnDecal {
+            position: wall_hit.position,
+            kind: DecalKind::BulletHole,
+        });
+    }
+}
+
+fn decals_on_damage(
+    mut damaged_events: EventReader<Damaged>,
+    mut decal_events: EventWriter<SpawnDecal>,
+) {
+    for damaged in damaged_events.iter() {
+        let Some(position) = damaged.position else {
+            continue;
+        };
+        decal_events.send(SpawnDecal {
+            position,
+            kind: DecalKind::Blood,
+        });
+    }
+}
+
+/// Scorch the floor under the barrel.
+fn decals_on_blast(
+    mut animation_events: EventReader<AnimationEvent>,
+    mut decal_events: EventWriter<SpawnDecal>,
+    shooter_query: Query<(&Weapon, &TextureAtlasSprite, &GlobalTransform)>,
+) {
+    for event in animation_events.iter() {
+        if event.tag != AnimationTag::MuzzleFlash {
+            continue;
+        }
+        let Ok((weapon, sprite, transform)) = shooter_query.get(event.entity) else {
+            continue;
+        };
+
+        let muzzle = barrel(weapon, sprite);
+        let position = transform
+            .transform_point(Vec3::new(muzzle.x, CHAR_HITBOX_Y_OFFSET, 0.))
+            .truncate();
+        decal_events.send(SpawnDecal {
+            position,
+            kind: DecalKind::Scorch,
+        });
+    }
+}
+
+fn spawn_decals(
+    mut commands: Commands,
+    mut rng: ResMut<EffectsRng>,
+    mut grid: ResMut<DecalGrid>,
+    mut decal_events: EventReader<SpawnDecal>,
+    layer_query: Query<Entity, With<DecalLayer>>,
+    decal_query: Query<&Sprite, With<Decal>>,
+) {
+    let Ok(layer) = layer_query.get_single() else {
+        return;
+    };
+
+    for SpawnDecal { position, kind } in decal_events.iter() {
+        let color = kind.color();
+        let (min_size, max_size) = kind.size();
+        let size = Vec2::new(
+            rng.gen_range(min_size..=max_size),
+            rng.gen_range(min_size..=max_size),
+        );
+        // the layer is at the origin: its space is the world's, but the depth
+        let transform = Transform::from_translation(position.extend(0.))
+            .with_rotation(Quat::from_rotation_z(rng.gen_range(0. ..TAU)));
+
+        let decal = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform,
+                    ..default()
+                },
+                Decal {
+                    kind: *kind,
+                    lifetime: Timer::from_seconds(DECAL_LIFETIME, TimerMode::Once),
+                },
+                Name::new(format!("Decal - {kind:?}")),
+            ))
+            .id();
+        commands.entity(layer).add_child(decal);
+
+        for old_decal in grid.insert(*position, decal) {
+            // spawned this frame
+            let Ok(sprite) = decal_query.get(old_decal) else {
+                commands.entity(old_decal).despawn_recursive();
+                continue;
+            };
+            fade_out(&mut commands, old_decal, sprite.color);
+        }
+    }
+}
+
+fn fade_old_decals(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    mut grid: ResMut<DecalGrid>,
+    mut decal_query: Query<(Entity, &mut Decal, &Transform, &Sprite)>,
+) {
+    for (entity, mut decal, transform, sprite) in &mut decal_query {
+        if !decal.lifetime.tick(game_time.delta()).just_finished() {
+            continue;
+        }
+        // already fading out if pushed out of its cell
+        if grid.remove(transform.translation.truncate(), entity) {
+            fade_out(&mut commands, entity, sprite.color);
+        }
+    }
+}
+
+fn fade_out(commands: &mut Commands, decal: Entity, color: Color) {
+    let mut transparent = color;
+    transparent.set_a(0.);
+    commands.entity(decal).insert(
+        Animator::new(Tween::new(
+            TweenTarget::SpriteColor {
+                start: color,
+                end: transparent,
+            },
+            DECAL_FADE_DURATION,
+        ))
+        .on_completed(OnCompleted::Despawn),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_cell_pushes_out_the_oldest() {
+        let mut grid = DecalGrid::default();
+        let position = Vec2::splat(DECAL_CELL_SIZE / 2.);
+        for index in 0..MAX_DECALS_PER_CELL as u32 {
+            assert!(grid.insert(position, Entity::from_raw(index)).is_empty());
+        }
+
+        let newest = Entity::from_raw(MAX_DECALS_PER_CELL as u32);
+        assert_eq!(grid.insert(position, newest), vec![Entity::from_raw(0)]);
+        // elsewhere
+        assert!(grid
+            .insert(position + DECAL_CELL_SIZE, Entity::from_raw(100))
+            .is_empty());
+
+        assert!(!grid.remove(position, Entity::from_raw(0)));
+        assert!(grid.remove(position, newest));
+        assert!(!grid.remove(position, newest));
+    }
+}
@@ -0,0 +1,166 @@
+//! Flash
+//!
+//! A [`Flash`] tints a sprite (`Sprite` or `TextureAtlasSprite`) then fades back
+//! to its original colour. The sprite colour multiplies the texture:
+//! channels above `1.` brighten it, so a flash can go all white.
+//!
+//! Inserting a new [`Flash`] during another one restarts it from the original colour,
+//! which is kept aside in a [`FlashBase`] until the last flash ends.
+//!
+//! Characters flash when [`Damaged`], with their [`HitFlash`] if any.
+
+use bevy::prelude::*;
+
+use crate::{
+    animations::easing::Easing,
+    combat::health::{apply_damage, Damaged},
+    constants::effects::{HIT_FLASH_DURATION, WHITE_FLASH},
+    hit_stop::{Frozen, GameTime},
+    juice::{juice_enabled, JuiceEffect, JuiceSettings},
+};
+
+pub struct FlashPlugin;
+
+impl Plugin for FlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                flash_on_damage
+                    .run_if(juice_enabled(JuiceEffect::Flashes))
+                    .after(apply_damage),
+                // always, to restore the colours when the juice is toggled off
+                update_flashes.after(flash_on_damage),
+            ),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Component)]
+#[reflect(Component)]
+pub struct Flash {
+    pub color: Color,
+    /// From the full flash (`0.`) to the original colour (`1.`).
+    pub curve: Easing,
+    pub timer: Timer,
+}
+
+impl Flash {
+    pub fn new(color: Color, duration: f32) -> Self {
+        Flash {
+            color,
+            curve: Easing::QuadraticIn,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+
+    pub fn with_curve(mut self, curve: Easing) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// How much of the flash colour shows, in `[0; 1]`.
+    pub fn weight(&self) -> f32 {
+        1. - self.curve.ease(self.timer.percent())
+    }
+}
+
+/// The flash a character shows when damaged.
+#[derive(Debug, Clone, Reflect, Component)]
+#[reflect(Component)]
+pub struct HitFlash {
+    pub color: Color,
+    /// in seconds
+    pub duration: f32,
+    pub curve: Easing,
+}
+
+impl Default for HitFlash {
+    fn default() -> Self {
+        HitFlash {
+            color: WHITE_FLASH,
+            duration: HIT_FLASH_DURATION,
+            curve: Easing::QuadraticIn,
+        }
+    }
+}
+
+impl HitFlash {
+    pub fn flash(&self) -> Flash {
+        Flash::new(self.color, self.duration).with_curve(self.curve)
+    }
+}
+
+/// The colour of a sprite before it flashed.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct FlashBase(pub Color);
+
+fn flash_on_damage(
+    mut commands: Commands,
+    mut damaged_events: EventReader<Damaged>,
+    hit_flash_query: Query<Option<&HitFlash>>,
+) {
+    for damaged in damaged_events.iter() {
+        // despawned by an earlier system
+        let Ok(hit_flash) = hit_flash_query.get(damaged.target) else {
+            continue;
+        };
+        let flash = hit_flash.cloned().unwrap_or_default().flash();
+        commands.entity(damaged.target).insert(flash);
+    }
+}
+
+fn update_flashes(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    juice_settings: Res<JuiceSettings>,
+    mut flash_query: Query<(
+        Entity,
+        &mut Flash,
+        Option<&FlashBase>,
+        Option<&mut Sprite>,
+        Option<&mut TextureAtlasSprite>,
+        Option<&Frozen>,
+    )>,
+) {
+    let intensity = juice_settings.intensity(JuiceEffect::Flashes).min(1.);
+
+    for (entity, mut flash, base, sprite, atlas_sprite, frozen) in &mut flash_query {
+        let color = match (sprite, atlas_sprite) {
+            (Some(sprite), _) => &mut sprite.into_inner().color,
+            (None, Some(atlas_sprite)) => &mut atlas_sprite.into_inner().color,
+            (None, None) => {
+                commands.entity(entity).remove::<Flash>();
+                continue;
+            }
+        };
+        let base = match base {
+            Some(FlashBase(base)) => *base,
+            None => {
+                commands.entity(entity).insert(FlashBase(*color));
+                *color
+            }
+        };
+
+        flash.timer.tick(game_time.delta_for(frozen));
+        if flash.timer.finished() {
+            *color = base;
+            commands.entity(entity).remove::<(Flash, FlashBase)>();
+            continue;
+        }
+
+        *color = mix(base, flash.color, flash.weight() * intensity);
+    }
+}
+
+/// Blend the RGB channels, keep `base`'s alpha (ie. an afterimage stays translucent).
+fn mix(base: Color, flash: Color, weight: f32) -> Color {
+    let [r, g, b, a] = base.as_rgba_f32();
+    let [flash_r, flash_g, flash_b, _] = flash.as_rgba_f32();
+    Color::rgba(
+        r + (flash_r - r) * weight,
+        g + (flash_g - g) * weight,
+        b + (flash_b - b) * weight,
+        a,
+    )
+}
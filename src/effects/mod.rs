@@ -0,0 +1,107 @@
+//! Visual Effects
+//!
+//! Feedback layered on top of the gameplay: it never changes the outcome of a fight.
+
+use std::{collections::VecDeque, marker::PhantomData};
+
+use bevy::prelude::*;
+
+pub mod damage_numbers;
+pub mod decals;
+pub mod flash;
+pub mod muzzle;
+pub mod particles;
+pub mod squash;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            damage_numbers::DamageNumbersPlugin,
+            decals::DecalsPlugin,
+            flash::FlashPlugin,
+            muzzle::MuzzlePlugin,
+            particles::ParticlesPlugin,
+            squash::SquashPlugin,
+        ));
+    }
+}
+
+/// A fixed set of entities with a `T`, spawned at startup and recycled:
+/// the oldest one is reused when they are all in use.
+#[derive(Debug, Resource)]
+pub struct Pool<T: Component> {
+    free: Vec<Entity>,
+    /// Oldest first.
+    used: VecDeque<Entity>,
+    marker: PhantomData<T>,
+}
+
+impl<T: Component> Default for Pool<T> {
+    fn default() -> Self {
+        Pool {
+            free: Vec::new(),
+            used: VecDeque::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Component> Pool<T> {
+    /// Add a newly spawned entity, free to use.
+    pub fn insert(&mut self, entity: Entity) {
+        self.free.push(entity);
+    }
+
+    /// A free entity, or the oldest one in use.
+    pub fn acquire(&mut self) -> Option<Entity> {
+        let entity = self.free.pop().or_else(|| self.used.pop_front())?;
+        self.used.push_back(entity);
+        Some(entity)
+    }
+
+    pub fn release(&mut self, entity: Entity) {
+        if let Some(index) = self.used.iter().position(|used| *used == entity) {
+            self.used.remove(index);
+            self.free.push(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Component)]
+    struct Pooled;
+
+    #[test]
+    fn full_pool_reuses_the_oldest() {
+        let mut pool = Pool::<Pooled>::default();
+        pool.insert(Entity::from_raw(1));
+        pool.insert(Entity::from_raw(0));
+
+        assert_eq!(pool.acquire(), Some(Entity::from_raw(0)));
+        assert_eq!(pool.acquire(), Some(Entity::from_raw(1)));
+        assert_eq!(pool.acquire(), Some(Entity::from_raw(0)));
+
+        pool.release(Entity::from_raw(1));
+        assert_eq!(pool.acquire(), Some(Entity::from_raw(1)));
+    }
+
+    #[test]
+    fn released_entity_is_reused_first() {
+        let mut pool = Pool::<Pooled>::default();
+        pool.insert(Entity::from_raw(0));
+        pool.insert(Entity::from_raw(1));
+
+        let first = pool.acquire().unwrap();
+        pool.release(first);
+        // releasing twice doesn't free it twice
+        pool.release(first);
+
+        assert_eq!(pool.acquire(), Some(first));
+        assert_ne!(pool.acquire(), Some(first));
+    }
+}
@@ -0,0 +1,267 @@
+//! Muzzle
+//!
+//! On the [`AnimationTag::MuzzleFlash`] notify of a shot, a [`MuzzleFlash`] bursts
+//! at the barrel of the [`Weapon`] (mirrored with the sprite) and a [`Casing`] is ejected.
+//!
+//! Casings are simulated, not physics bodies: they fly up with a fake height,
+//! tumble, bounce once on the floor and rest there for a while before fading out.
+//! Only [`MAX_CASINGS`] stay at once: the oldest ones fade out first.
+
+use std::{collections::VecDeque, f32::consts::PI};
+
+use bevy::{prelude::*, transform::TransformSystem};
+use rand::Rng;
+
+use crate::{
+    animations::{
+        easing::Easing,
+        sprite_sheet_animation::{jump_frame_character_state, AnimationEvent, AnimationTag},
+        tween::{Animator, OnCompleted, Tween, TweenSet, TweenTarget, Tweenable},
+    },
+    combat::shoot::Weapon,
+    constants::{
+        character::CHAR_HITBOX_Y_OFFSET,
+        effects::{
+            CASING_COLOR, CASING_EJECTION_POINT, CASING_EJECTION_SPEED, CASING_FADE_DURATION,
+            CASING_FRICTION, CASING_GRAVITY, CASING_LIFT, CASING_LINGER, CASING_RESTITUTION,
+            CASING_SIZE, CASING_SPIN, MAX_CASINGS, MUZZLE_FLASH_COLOR, MUZZLE_FLASH_DURATION,
+            MUZZLE_FLASH_SIZE,
+        },
+        layers::CASINGS_Z,
+    },
+    hit_stop::GameTime,
+    juice::{juice_enabled, JuiceEffect},
+    replay::EffectsRng,
+};
+
+pub struct MuzzlePlugin;
+
+impl Plugin for MuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Casings>().add_systems(
+            PostUpdate,
+            (
+                (
+                    muzzle_flash.run_if(juice_enabled(JuiceEffect::Flashes)),
+                    eject_casings.run_if(juice_enabled(JuiceEffect::Particles)),
+                )
+                    .after(jump_frame_character_state)
+                    .before(TweenSet),
+                // always, to let the ejected casings land when the juice is toggled off
+                update_casings.before(TweenSet),
+            )
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// A short flash at the barrel, follows the shooter.
+#[derive(Debug, Component)]
+pub struct MuzzleFlash;
+
+#[derive(Debug, Default, Component)]
+pub struct Casing {
+    /// The point of the floor under the casing.
+    ground: Vec2,
+    /// Along the floor.
+    velocity: Vec2,
+    /// Above the floor.
+    height: f32,
+    vertical_velocity: f32,
+    /// radian per second
+    spin: f32,
+    bounced: bool,
+    resting: bool,
+    linger: Timer,
+}
+
+/// The casings that aren't fading out yet, oldest first.
+#[derive(Debug, Default, Deref, DerefMut, Resource)]
+pub struct Casings(VecDeque<Entity>);
+
+/// The muzzle offset of the weapon, mirrored with the sprite.
+pub fn barrel(weapon: &Weapon, sprite: &TextureAtlasSprite) -> Vec2 {
+    let facing = if sprite.flip_x { -1. } else { 1. };
+    Vec2::new(weapon.muzzle_offset.x * facing, weapon.muzzle_offset.y)
+}
+
+fn muzzle_flash(
+    mut commands: Commands,
+    mut animation_events: EventReader<AnimationEvent>,
+    shooter_query: Query<(&Weapon, &TextureAtlasSprite)>,
+) {
+    for event in animation_events.iter() {
+        if event.tag != AnimationTag::MuzzleFlash {
+            continue;
+        }
+        let Ok((weapon, sprite)) = shooter_query.get(event.entity) else {
+            continue;
+        };
+
+        let mut transparent = MUZZLE_FLASH_COLOR;
+        transparent.set_a(0.);
+        let shrink = Tween::new(
+            TweenTarget::Scale {
+                start: Vec3::ONE,
+                end: Vec3::splat(0.2),
+            },
+            MUZZLE_FLASH_DURATION,
+        )
+        .with_easing(Easing::QuadraticIn);
+        let fade = Tween::new(
+            TweenTarget::SpriteColor {
+                start: MUZZLE_FLASH_COLOR,
+                end: transparent,
+            },
+            MUZZLE_FLASH_DURATION,
+        );
+
+        let muzzle_flash = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: MUZZLE_FLASH_COLOR,
+                        custom_size: Some(MUZZLE_FLASH_SIZE),
+                        flip_x: sprite.flip_x,
+                        ..default()
+                    },
+                    // in front of the shooter
+                    transform: Transform::from_translation(barrel(weapon, sprite).extend(0.1)),
+                    ..default()
+                },
+                MuzzleFlash,
+                Animator::new(Tweenable::from(shrink).with(fade))
+                    .on_completed(OnCompleted::Despawn),
+                Name::new("Muzzle Flash"),
+            ))
+            .id();
+        commands.entity(event.entity).add_child(muzzle_flash);
+    }
+}
+
+fn eject_casings(
+    mut commands: Commands,
+    mut rng: ResMut<EffectsRng>,
+    mut casings: ResMut<Casings>,
+    mut animation_events: EventReader<AnimationEvent>,
+    shooter_query: Query<(&Weapon, &TextureAtlasSprite, &GlobalTransform)>,
+    sprite_query: Query<&Sprite, With<Casing>>,
+) {
+    for event in animation_events.iter() {
+        if event.tag != AnimationTag::MuzzleFlash {
+            continue;
+        }
+        let Ok((weapon, sprite, transform)) = shooter_query.get(event.entity) else {
+            continue;
+        };
+
+        let ejection = barrel(weapon, sprite) * CASING_EJECTION_POINT;
+        let position = transform.transform_point(ejection.extend(0.)).truncate();
+        let feet = transform
+            .transform_point(Vec3::new(0., CHAR_HITBOX_Y_OFFSET, 0.))
+            .truncate();
+        // thrown out of the back of the weapon, a bit up or down the floor
+        let backward = if sprite.flip_x { 1. } else { -1. };
+        let velocity = Vec2::new(
+            backward * rng.gen_range(CASING_EJECTION_SPEED.0..=CASING_EJECTION_SPEED.1),
+            rng.gen_range(-0.3_f32..=0.3) * CASING_EJECTION_SPEED.0,
+        );
+        let spin = backward * rng.gen_range(CASING_SPIN.0..=CASING_SPIN.1);
+
+        let casing = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: CASING_COLOR,
+                        custom_size: Some(CASING_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position.extend(CASINGS_Z))
+                        .with_rotation(Quat::from_rotation_z(rng.gen_range(0. ..PI))),
+                    ..default()
+                },
+                Casing {
+                    ground: Vec2::new(position.x, feet.y),
+                    velocity,
+                    height: (position.y - feet.y).max(0.),
+                    vertical_velocity: rng.gen_range(CASING_LIFT.0..=CASING_LIFT.1),
+                    spin,
+                    linger: Timer::from_seconds(CASING_LINGER, TimerMode::Once),
+                    ..default()
+                },
+                Name::new("Casing"),
+            ))
+            .id();
+        casings.push_back(casing);
+
+        while casings.len() > MAX_CASINGS {
+            let Some(oldest) = casings.pop_front() else {
+                break;
+            };
+            if let Ok(sprite) = sprite_query.get(oldest) {
+                fade_out(&mut commands, oldest, sprite.color);
+            }
+        }
+    }
+}
+
+/// Fly, bounce once, then rest until fading out.
+fn update_casings(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    mut casings: ResMut<Casings>,
+    mut casing_query: Query<(Entity, &mut Casing, &mut Transform, &Sprite)>,
+) {
+    let delta = game_time.delta_seconds();
+
+    for (entity, mut casing, mut transform, sprite) in &mut casing_query {
+        if casing.resting {
+            casing.linger.tick(game_time.delta());
+            // unless already fading out, pushed by newer casings
+            if casing.linger.just_finished() {
+                if let Some(index) = casings.iter().position(|casing| *casing == entity) {
+                    casings.remove(index);
+                    fade_out(&mut commands, entity, sprite.color);
+                }
+            }
+            continue;
+        }
+
+        casing.vertical_velocity -= CASING_GRAVITY * delta;
+        casing.height += casing.vertical_velocity * delta;
+        let velocity = casing.velocity;
+        casing.ground += velocity * delta;
+        transform.rotate_z(casing.spin * delta);
+
+        if casing.height <= 0. {
+            casing.height = 0.;
+            if casing.bounced {
+                casing.resting = true;
+            } else {
+                casing.bounced = true;
+                casing.vertical_velocity *= -CASING_RESTITUTION;
+                casing.velocity *= CASING_FRICTION;
+                casing.spin *= -CASING_FRICTION;
+            }
+        }
+
+        let position = casing.ground + Vec2::Y * casing.height;
+        transform.translation = position.extend(CASINGS_Z);
+    }
+}
+
+/// Despawned when faded out, see [`OnCompleted::Despawn`].
+fn fade_out(commands: &mut Commands, casing: Entity, color: Color) {
+    let mut transparent = color;
+    transparent.set_a(0.);
+    commands.entity(casing).insert(
+        Animator::new(Tween::new(
+            TweenTarget::SpriteColor {
+                start: color,
+                end: transparent,
+            },
+            CASING_FADE_DURATION,
+        ))
+        .on_completed(OnCompleted::Despawn),
+    );
+}
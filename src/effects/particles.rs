@@ -0,0 +1,402 @@
+//! Particles
+//!
+//! An [`EmitParticles`] bursts a few CPU sprite [`Particle`]s, described by a [`ParticleEmitter`].
+//! They fly along a direction, slow down with drag (no gravity: the view is top-down),
+//! and change size and colour over their life.
+//!
+//! Particles are sprites of a [`Pool`], hidden when dead.
+//! Their randomness comes from the [`EffectsRng`], so a seeded run emits the same particles.
+//!
+//! Characters kick up dust on their footsteps, and skid in a cloud when they stop from full speed.
+
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_rapier2d::prelude::Velocity;
+use rand::Rng;
+
+use crate::{
+    animations::{
+        easing::Easing,
+        sprite_sheet_animation::{animate_character, AnimationEvent, AnimationTag},
+        tween::lerp_color,
+    },
+    constants::{
+        character::CHAR_HITBOX_Y_OFFSET,
+        effects::{FOOTSTEP_DUST, PARTICLE_POOL_SIZE, SKID_CLOUD},
+        layers::PARTICLES_Z,
+    },
+    effects::Pool,
+    hit_stop::GameTime,
+    juice::{juice_enabled, JuiceEffect, JuiceSettings},
+    movement::Skidded,
+    replay::EffectsRng,
+};
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pool<Particle>>()
+            .add_event::<EmitParticles>()
+            .add_systems(Startup, spawn_particle_pool)
+            .add_systems(
+                PostUpdate,
+                (
+                    (footstep_dust, skid_cloud)
+                        .run_if(juice_enabled(JuiceEffect::Particles))
+                        .after(animate_character),
+                    emit_particles,
+                    update_particles,
+                )
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// What a burst of particles looks like. Ranges are `(min, max)`, picked at random.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleEmitter {
+    pub count: (u32, u32),
+    /// in world units per second
+    pub speed: (f32, f32),
+    /// Half-angle around the direction, in radian.
+    pub spread: f32,
+    /// in seconds
+    pub lifetime: (f32, f32),
+    /// per second, exponential
+    pub drag: f32,
+    /// From birth to death, in world units.
+    pub size: (f32, f32),
+    pub size_curve: Easing,
+    /// From birth to death.
+    pub color: (Color, Color),
+    /// Particles are born in this disc around the position.
+    pub radius: f32,
+}
+
+impl ParticleEmitter {
+    /// Fewer (or more) particles, `0.` emitting none.
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        let scale = |count: u32| (count as f32 * intensity.max(0.)).round() as u32;
+        self.count = (scale(self.count.0), scale(self.count.1));
+        self
+    }
+
+    /// The particles (and their position) of a burst at `position` along `direction`.
+    ///
+    /// A zero `direction` throws them all around.
+    pub fn burst(
+        &self,
+        position: Vec2,
+        direction: Vec2,
+        rng: &mut impl Rng,
+    ) -> Vec<(Vec2, Particle)> {
+        let count = rng.gen_range(self.count.0..=self.count.1.max(self.count.0));
+        let (base_angle, spread) = match direction.try_normalize() {
+            Some(direction) => (direction.y.atan2(direction.x), self.spread),
+            None => (0., TAU / 2.),
+        };
+
+        (0..count)
+            .map(|_| {
+                let offset = Vec2::from_angle(rng.gen_range(0. ..TAU))
+                    * self.radius
+                    * rng.gen_range(0_f32..=1.).sqrt();
+                let angle = base_angle + rng.gen_range(-spread..=spread);
+                let speed = rng.gen_range(self.speed.0..=self.speed.1.max(self.speed.0));
+                let lifetime =
+                    rng.gen_range(self.lifetime.0..=self.lifetime.1.max(self.lifetime.0));
+
+                (
+                    position + offset,
+                    Particle {
+                        velocity: Vec2::from_angle(angle) * speed,
+                        drag: self.drag,
+                        age: 0.,
+                        lifetime,
+                        size: self.size,
+                        size_curve: self.size_curve,
+                        color: self.color,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Burst particles at `position`, in world space.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct EmitParticles {
+    pub position: Vec2,
+    pub direction: Vec2,
+    pub emitter: ParticleEmitter,
+}
+
+/// A pooled sprite, hidden when dead.
+#[derive(Debug, Default, Clone, PartialEq, Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub drag: f32,
+    /// in seconds
+    pub age: f32,
+    /// in seconds
+    pub lifetime: f32,
+    pub size: (f32, f32),
+    pub size_curve: Easing,
+    pub color: (Color, Color),
+}
+
+impl Particle {
+    /// From `0.` (birth) to `1.` (death).
+    fn life(&self) -> f32 {
+        if self.lifetime > 0. {
+            (self.age / self.lifetime).min(1.)
+        } else {
+            1.
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.age >= self.lifetime
+    }
+
+    pub fn size(&self) -> f32 {
+        let (start, end) = self.size;
+        start + (end - start) * self.size_curve.ease(self.life())
+    }
+
+    pub fn color(&self) -> Color {
+        lerp_color(self.color.0, self.color.1, self.life())
+    }
+
+    /// Age by `delta` seconds, returns the distance travelled.
+    pub fn step(&mut self, delta: f32) -> Vec2 {
+        self.age += delta;
+        self.velocity *= (-self.drag * delta).exp();
+        self.velocity * delta
+    }
+}
+
+fn spawn_particle_pool(mut commands: Commands, mut pool: ResMut<Pool<Particle>>) {
+    for _ in 0..PARTICLE_POOL_SIZE {
+        let particle = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::ONE),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                Particle::default(),
+                Name::new("Particle"),
+            ))
+            .id();
+        pool.insert(particle);
+    }
+}
+
+/// Dust behind the feet, on each footstep notify.
+fn footstep_dust(
+    mut animation_events: EventReader<AnimationEvent>,
+    mut particle_events: EventWriter<EmitParticles>,
+    character_query: Query<(&GlobalTransform, Option<&Velocity>)>,
+) {
+    for event in animation_events.iter() {
+        if event.tag != AnimationTag::Footstep {
+            continue;
+        }
+        let Ok((transform, velocity)) = character_query.get(event.entity) else {
+            continue;
+        };
+
+        particle_events.send(EmitParticles {
+            position: feet(transform),
+            direction: -velocity.map_or(Vec2::ZERO, |velocity| velocity.linvel),
+            emitter: FOOTSTEP_DUST,
+        });
+    }
+}
+
+/// A cloud ahead of the feet, thrown forward by the braking.
+fn skid_cloud(
+    mut skid_events: EventReader<Skidded>,
+    mut particle_events: EventWriter<EmitParticles>,
+    transform_query: Query<&GlobalTransform>,
+) {
+    for skidded in skid_events.iter() {
+        let Ok(transform) = transform_query.get(skidded.entity) else {
+            continue;
+        };
+
+        particle_events.send(EmitParticles {
+            position: feet(transform),
+            direction: skidded.velocity,
+            emitter: SKID_CLOUD,
+        });
+    }
+}
+
+/// The feet of a character, in world space.
+fn feet(transform: &GlobalTransform) -> Vec2 {
+    transform
+        .transform_point(Vec3::new(0., CHAR_HITBOX_Y_OFFSET, 0.))
+        .truncate()
+}
+
+fn emit_particles(
+    juice_settings: Res<JuiceSettings>,
+    mut rng: ResMut<EffectsRng>,
+    mut particle_events: EventReader<EmitParticles>,
+    mut pool: ResMut<Pool<Particle>>,
+    mut particle_query: Query<(&mut Particle, &mut Sprite, &mut Transform, &mut Visibility)>,
+) {
+    let intensity = juice_settings.intensity(JuiceEffect::Particles);
+
+    for EmitParticles {
+        position,
+        direction,
+        emitter,
+    } in particle_events.iter()
+    {
+        let emitter = emitter.with_intensity(intensity);
+        for (position, new_particle) in emitter.burst(*position, *direction, &mut **rng) {
+            // an empty pool, but the next events are still read
+            let Some(entity) = pool.acquire() else {
+                break;
+            };
+            let Ok((mut particle, mut sprite, mut transform, mut visibility)) =
+                particle_query.get_mut(entity)
+            else {
+                continue;
+            };
+
+            sprite.color = new_particle.color();
+            transform.translation = position.extend(PARTICLES_Z);
+            transform.scale = Vec3::splat(new_particle.size());
+            *particle = new_particle;
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+fn update_particles(
+    game_time: Res<GameTime>,
+    mut pool: ResMut<Pool<Particle>>,
+    mut particle_query: Query<(
+        Entity,
+        &mut Particle,
+        &mut Sprite,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+) {
+    let delta = game_time.delta_seconds();
+
+    for (entity, mut particle, mut sprite, mut transform, mut visibility) in &mut particle_query {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        transform.translation += particle.step(delta).extend(0.);
+        if particle.is_dead() {
+            *visibility = Visibility::Hidden;
+            pool.release(entity);
+            continue;
+        }
+
+        transform.scale = Vec3::splat(particle.size());
+        sprite.color = particle.color();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn same_seed_same_burst() {
+        let burst = |seed| {
+            SKID_CLOUD.burst(
+                Vec2::new(3., -2.),
+                Vec2::X,
+                &mut StdRng::seed_from_u64(seed),
+            )
+        };
+
+        assert_eq!(burst(42), burst(42));
+        assert_ne!(burst(42), burst(43));
+    }
+
+    #[test]
+    fn burst_follows_the_emitter() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let position = Vec2::new(10., 5.);
+
+        for _ in 0..100 {
+            let burst = SKID_CLOUD.burst(position, Vec2::Y, &mut rng);
+            assert!((SKID_CLOUD.count.0..=SKID_CLOUD.count.1).contains(&(burst.len() as u32)));
+
+            for (particle_position, particle) in burst {
+                assert!(particle_position.distance(position) <= SKID_CLOUD.radius + 1e-4);
+
+                let speed = particle.velocity.length();
+                assert!(speed >= SKID_CLOUD.speed.0 - 1e-4 && speed <= SKID_CLOUD.speed.1 + 1e-4);
+                assert!(particle.velocity.angle_between(Vec2::Y).abs() <= SKID_CLOUD.spread + 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn intensity_scales_the_count() {
+        assert_eq!(SKID_CLOUD.with_intensity(1.), SKID_CLOUD);
+        assert_eq!(SKID_CLOUD.with_intensity(2.).count, (12, 18));
+        assert_eq!(SKID_CLOUD.with_intensity(0.5).count, (3, 5));
+    }
+
+    #[test]
+    fn no_particles_at_zero_intensity() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for emitter in [FOOTSTEP_DUST, SKID_CLOUD] {
+            let emitter = emitter.with_intensity(0.);
+            assert_eq!(emitter.count, (0, 0));
+            assert!(emitter.burst(Vec2::ZERO, Vec2::X, &mut rng).is_empty());
+        }
+    }
+
+    #[test]
+    fn particles_slow_down_and_fade_out() {
+        let mut particle = Particle {
+            velocity: Vec2::new(10., 0.),
+            drag: 4.,
+            lifetime: 1.,
+            size: (2., 4.),
+            size_curve: Easing::Linear,
+            color: (Color::rgba(1., 1., 1., 1.), Color::rgba(1., 1., 1., 0.)),
+            ..default()
+        };
+        assert_eq!(particle.size(), 2.);
+
+        let first = particle.step(0.1).length();
+        let second = particle.step(0.1).length();
+        assert!(second < first);
+
+        for _ in 0..3 {
+            particle.step(0.1);
+        }
+        assert!((particle.size() - 3.).abs() < 1e-4);
+        assert!((particle.color().a() - 0.5).abs() < 1e-4);
+        assert!(!particle.is_dead());
+
+        particle.step(0.6);
+        assert!(particle.is_dead());
+        assert_eq!(particle.size(), 4.);
+        assert_eq!(particle.color().a(), 0.);
+    }
+}
@@ -0,0 +1,185 @@
+//! Squash & Stretch
+//!
+//! A character's sprite deforms on some state transitions (ie. starting and stopping a run,
+//! dashing, getting hit) and springs back to its base scale (ie. [`PLAYER_SCALE`]).
+//! The deformation keeps the area: a squash is wider and shorter, a stretch thinner and taller.
+//!
+//! The transform is scaled, never moved (it belongs to the physics body):
+//! the feet stay on the ground as the sprite's anchor moves with the deformation.
+//! The colliders of the character keep their shape with a [`ColliderScale::Absolute`].
+//!
+//! [`PLAYER_SCALE`]: crate::constants::character::player::PLAYER_SCALE
+//! [`ColliderScale::Absolute`]: bevy_rapier2d::prelude::ColliderScale::Absolute
+//!
+//! Toggling the squash off restores the rest shape of the sprites caught mid-spring.
+
+use bevy::{prelude::*, sprite::Anchor, transform::TransformSystem};
+
+use crate::{
+    animations::sprite_sheet_animation::CharacterState,
+    combat::health::apply_damage,
+    constants::{
+        character::CHAR_FEET_ANCHOR,
+        effects::{
+            DASH_END_SQUASH, DASH_SQUASH, HIT_SQUASH, MAX_SQUASH, RUN_START_SQUASH,
+            RUN_STOP_SQUASH, SHOOT_SQUASH, SQUASH_DAMPING, SQUASH_STIFFNESS,
+        },
+    },
+    hit_stop::{Frozen, GameTime},
+    juice::{juice_enabled, JuiceEffect, JuiceSettings},
+};
+
+pub struct SquashPlugin;
+
+impl Plugin for SquashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                // every state is set, including the stagger
+                (squash_on_state_change, animate_squash)
+                    .chain()
+                    .after(apply_damage)
+                    .run_if(juice_enabled(JuiceEffect::Squash)),
+                reset_squash
+                    .run_if(resource_changed::<JuiceSettings>())
+                    .run_if(not(juice_enabled(JuiceEffect::Squash))),
+            )
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Component)]
+#[reflect(Component)]
+pub struct SquashStretch {
+    /// Positive squashes (wider, shorter), negative stretches (thinner, taller).
+    pub deformation: f32,
+    pub velocity: f32,
+    /// Pull of the spring towards the base scale.
+    pub stiffness: f32,
+    pub damping: f32,
+    /// Height of the feet in the sprite, from `-0.5` (bottom) to `0.5` (top).
+    pub feet_anchor: f32,
+    /// Captured the first frame.
+    base_scale: Option<Vec2>,
+    previous_state: Option<CharacterState>,
+}
+
+impl Default for SquashStretch {
+    fn default() -> Self {
+        SquashStretch {
+            deformation: 0.,
+            velocity: 0.,
+            stiffness: SQUASH_STIFFNESS,
+            damping: SQUASH_DAMPING,
+            feet_anchor: CHAR_FEET_ANCHOR,
+            base_scale: None,
+            previous_state: None,
+        }
+    }
+}
+
+impl SquashStretch {
+    pub fn squash(&mut self, amount: f32) {
+        self.deformation = (self.deformation + amount).clamp(-MAX_SQUASH, MAX_SQUASH);
+    }
+
+    /// The deformation to add on the transition, if any.
+    fn transition(previous: CharacterState, current: CharacterState) -> Option<f32> {
+        use CharacterState::*;
+        match (previous, current) {
+            (Idle, Run) => Some(RUN_START_SQUASH),
+            (Run, Idle) => Some(RUN_STOP_SQUASH),
+            (Dash, Dash) => None,
+            (_, Dash) => Some(DASH_SQUASH),
+            (Dash, _) => Some(DASH_END_SQUASH),
+            (_, Hit) => Some(HIT_SQUASH),
+            (_, Shoot) => Some(SHOOT_SQUASH),
+            _ => None,
+        }
+    }
+}
+
+fn squash_on_state_change(
+    juice_settings: Res<JuiceSettings>,
+    mut squash_query: Query<(&CharacterState, &mut SquashStretch), Changed<CharacterState>>,
+) {
+    let intensity = juice_settings.intensity(JuiceEffect::Squash);
+
+    for (character_state, mut squash_stretch) in &mut squash_query {
+        let previous = squash_stretch.previous_state.replace(*character_state);
+        if let Some(amount) =
+            previous.and_then(|previous| SquashStretch::transition(previous, *character_state))
+        {
+            squash_stretch.squash(amount * intensity);
+        }
+    }
+}
+
+/// Spring the deformation back to `0.` and scale the sprite around its feet.
+fn animate_squash(
+    game_time: Res<GameTime>,
+    mut squash_query: Query<(
+        &mut SquashStretch,
+        &mut Transform,
+        &mut TextureAtlasSprite,
+        Option<&Frozen>,
+    )>,
+) {
+    for (mut squash_stretch, mut transform, mut sprite, frozen) in &mut squash_query {
+        let base_scale = *squash_stretch
+            .base_scale
+            .get_or_insert(transform.scale.truncate());
+        let delta = game_time.delta_for(frozen).as_secs_f32();
+
+        // semi-implicit Euler
+        let acceleration = -squash_stretch.stiffness * squash_stretch.deformation
+            - squash_stretch.damping * squash_stretch.velocity;
+        squash_stretch.velocity += acceleration * delta;
+        let deformation = (squash_stretch.deformation + squash_stretch.velocity * delta)
+            .clamp(-MAX_SQUASH, MAX_SQUASH);
+        squash_stretch.deformation = deformation;
+
+        if deformation.abs() < 1e-4 {
+            rest(base_scale, &mut transform, &mut sprite);
+            continue;
+        }
+
+        let stretch = Vec2::new(1. + deformation, 1. / (1. + deformation));
+        let scale = base_scale * stretch;
+        transform.scale.x = scale.x;
+        transform.scale.y = scale.y;
+        // the point of the sprite at the origin, so that the feet don't move
+        sprite.anchor = Anchor::Custom(Vec2::new(
+            0.,
+            squash_stretch.feet_anchor * (1. - 1. / stretch.y),
+        ));
+    }
+}
+
+/// Back to the base scale, without a spring.
+fn reset_squash(
+    mut squash_query: Query<(&mut SquashStretch, &mut Transform, &mut TextureAtlasSprite)>,
+) {
+    for (mut squash_stretch, mut transform, mut sprite) in &mut squash_query {
+        squash_stretch.deformation = 0.;
+        squash_stretch.velocity = 0.;
+        // the states changed meanwhile aren't transitions
+        squash_stretch.previous_state = None;
+        if let Some(base_scale) = squash_stretch.base_scale {
+            rest(base_scale, &mut transform, &mut sprite);
+        }
+    }
+}
+
+/// Only written if deformed, not to trigger change detection every frame.
+fn rest(base_scale: Vec2, transform: &mut Mut<Transform>, sprite: &mut Mut<TextureAtlasSprite>) {
+    if transform.scale.truncate() != base_scale {
+        transform.scale.x = base_scale.x;
+        transform.scale.y = base_scale.y;
+    }
+    if !matches!(sprite.anchor, Anchor::Center) {
+        sprite.anchor = Anchor::Center;
+    }
+}
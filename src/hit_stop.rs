@@ -0,0 +1,308 @@
+//! Hit-Stop
+//!
+//! Short freezes (or slow-downs) on impact.
+//!
+//! Gameplay (animations, timers, physics) reads the scaled [`GameTime`] instead of the raw
+//! [`Time`], which keeps running for the UI and the debug inspector.
+//! The [`GameTime`] is paused outside of [`GameState::Playing`].
+//! A [`HitStop`] can either affect the whole game or only some entities
+//! (ie. the attacker and the victim), which get [`Frozen`] for a while.
+//!
+//! With a [`GameTime::fixed_step`] (ie. to record or replay a session),
+//! frames last exactly that long whatever the real frame rate, and so does the physics step.
+
+use bevy::{prelude::*, time::TimeSystem};
+use bevy_rapier2d::prelude::*;
+use std::time::Duration;
+
+use crate::{
+    juice::{juice_enabled, JuiceEffect, JuiceSettings},
+    playing, GameState,
+};
+
+pub struct HitStopPlugin;
+
+impl Plugin for HitStopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameTime>()
+            .add_event::<HitStop>()
+            .add_systems(First, update_game_time.after(TimeSystem))
+            .add_systems(
+                PostUpdate,
+                (
+                    trigger_hit_stop.run_if(juice_enabled(JuiceEffect::HitStop)),
+                    scale_physics_time,
+                    freeze_velocity,
+                    unfreeze_entities,
+                )
+                    .chain()
+                    .before(PhysicsSet::SyncBackend),
+            );
+    }
+}
+
+/// The gameplay clock.
+///
+/// Follows the real [`Time`] but can be slowed down or paused by a global [`HitStop`].
+#[derive(Debug, Reflect, Resource)]
+pub struct GameTime {
+    delta: Duration,
+    elapsed: Duration,
+    scale: f32,
+    /// The unscaled duration of the frame, which ticks hit-stops.
+    raw_delta: Duration,
+    /// Replaces the real frame duration when set.
+    fixed_step: Option<Duration>,
+    /// The current global hit-stop, ticked on the unscaled frame duration.
+    hit_stop: Option<(Timer, f32)>,
+}
+
+impl Default for GameTime {
+    fn default() -> Self {
+        GameTime {
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            scale: 1.,
+            raw_delta: Duration::ZERO,
+            fixed_step: None,
+            hit_stop: None,
+        }
+    }
+}
+
+impl GameTime {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// `1.` at normal speed, `0.` during a global freeze.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// The duration of the frame, ignoring hit-stops.
+    ///
+    /// The real frame duration, or the [fixed step](Self::fixed_step).
+    pub fn raw_delta(&self) -> Duration {
+        self.raw_delta
+    }
+
+    pub fn fixed_step(&self) -> Option<Duration> {
+        self.fixed_step
+    }
+
+    /// Make every frame last `fixed_step`, so a session can be reproduced exactly.
+    pub fn set_fixed_step(&mut self, fixed_step: Option<Duration>) {
+        self.fixed_step = fixed_step;
+    }
+
+    /// Move the clock forward by a frame of `delta`, without a hit-stop.
+    #[cfg(test)]
+    pub fn advance(&mut self, delta: Duration) {
+        self.raw_delta = delta;
+        self.delta = delta;
+        self.elapsed += delta;
+    }
+
+    /// The delta time of a specific entity, taking its [`Frozen`] state into account.
+    pub fn delta_for(&self, frozen: Option<&Frozen>) -> Duration {
+        match frozen {
+            Some(frozen) => self.delta.mul_f32(frozen.time_scale),
+            None => self.delta,
+        }
+    }
+}
+
+/// Pause or slow gameplay time for a short duration.
+#[derive(Debug, Clone, Event)]
+pub struct HitStop {
+    pub duration: Duration,
+    /// `0.` freezes, `0.2` is a heavy slow-motion.
+    pub time_scale: f32,
+    pub target: HitStopTarget,
+}
+
+#[derive(Debug, Clone)]
+pub enum HitStopTarget {
+    Global,
+    /// Only these entities stop (ie. the attacker and the victim).
+    Entities(Vec<Entity>),
+}
+
+impl HitStop {
+    /// Freeze the whole game for `millis` milliseconds.
+    pub fn global(millis: u64) -> Self {
+        HitStop {
+            duration: Duration::from_millis(millis),
+            time_scale: 0.,
+            target: HitStopTarget::Global,
+        }
+    }
+
+    /// Freeze only the given entities for `millis` milliseconds.
+    pub fn entities(millis: u64, entities: impl IntoIterator<Item = Entity>) -> Self {
+        HitStop {
+            duration: Duration::from_millis(millis),
+            time_scale: 0.,
+            target: HitStopTarget::Entities(entities.into_iter().collect()),
+        }
+    }
+
+    /// Slow down instead of freezing.
+    pub fn with_time_scale(mut self, time_scale: f32) -> Self {
+        self.time_scale = time_scale;
+        self
+    }
+}
+
+/// This entity is (partially) stopped by a [`HitStop`].
+#[derive(Debug, Reflect, Component)]
+pub struct Frozen {
+    /// Ticked on [`GameTime::raw_delta`].
+    pub timer: Timer,
+    pub time_scale: f32,
+    /// The velocity the entity had before being frozen, restored afterwards.
+    velocity: Option<Velocity>,
+}
+
+fn update_game_time(
+    time: Res<Time>,
+    game_state: Res<State<GameState>>,
+    mut game_time: ResMut<GameTime>,
+) {
+    if !playing(game_state) {
+        game_time.scale = 0.;
+        game_time.delta = Duration::ZERO;
+        game_time.raw_delta = Duration::ZERO;
+        return;
+    }
+
+    let raw_delta = game_time.fixed_step.unwrap_or(time.delta());
+    game_time.raw_delta = raw_delta;
+
+    let mut scale = 1.;
+    if let Some((timer, time_scale)) = &mut game_time.hit_stop {
+        timer.tick(raw_delta);
+        scale = *time_scale;
+    }
+    if game_time
+        .hit_stop
+        .as_ref()
+        .map_or(false, |(timer, _)| timer.finished())
+    {
+        game_time.hit_stop = None;
+        scale = 1.;
+    }
+
+    game_time.scale = scale;
+    game_time.delta = raw_delta.mul_f32(scale);
+    game_time.elapsed += game_time.delta;
+}
+
+fn trigger_hit_stop(
+    mut commands: Commands,
+    juice_settings: Res<JuiceSettings>,
+    mut hit_stop_events: EventReader<HitStop>,
+    mut game_time: ResMut<GameTime>,
+    mut frozen_query: Query<&mut Frozen>,
+    velocity_query: Query<&Velocity>,
+) {
+    for HitStop {
+        duration,
+        time_scale,
+        target,
+    } in hit_stop_events.iter()
+    {
+        let duration = &duration.mul_f32(juice_settings.intensity(JuiceEffect::HitStop));
+        match target {
+            HitStopTarget::Global => {
+                // overlapping hit-stops: keep the longest and the strongest
+                let (remaining, scale) = match &game_time.hit_stop {
+                    Some((timer, scale)) => {
+                        (timer.remaining().max(*duration), scale.min(*time_scale))
+                    }
+                    None => (*duration, *time_scale),
+                };
+                game_time.hit_stop = Some((Timer::new(remaining, TimerMode::Once), scale));
+            }
+            HitStopTarget::Entities(entities) => {
+                for entity in entities {
+                    if let Ok(mut frozen) = frozen_query.get_mut(*entity) {
+                        let remaining = frozen.timer.remaining().max(*duration);
+                        frozen.timer = Timer::new(remaining, TimerMode::Once);
+                        frozen.time_scale = frozen.time_scale.min(*time_scale);
+                    } else if let Some(mut entity_commands) = commands.get_entity(*entity) {
+                        entity_commands.insert(Frozen {
+                            timer: Timer::new(*duration, TimerMode::Once),
+                            time_scale: *time_scale,
+                            velocity: velocity_query.get(*entity).ok().copied(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The physics step follows the gameplay clock.
+fn scale_physics_time(game_time: Res<GameTime>, mut rapier_config: ResMut<RapierConfiguration>) {
+    if let Some(fixed_step) = game_time.fixed_step() {
+        let dt = fixed_step.as_secs_f32() * game_time.scale();
+        match &mut rapier_config.timestep_mode {
+            TimestepMode::Fixed { dt: fixed_dt, .. } if *fixed_dt == dt => {}
+            timestep_mode => *timestep_mode = TimestepMode::Fixed { dt, substeps: 1 },
+        }
+        return;
+    }
+
+    match &mut rapier_config.timestep_mode {
+        TimestepMode::Variable { time_scale, .. }
+        | TimestepMode::Interpolated { time_scale, .. } => {
+            if *time_scale != game_time.scale() {
+                *time_scale = game_time.scale();
+            }
+        }
+        TimestepMode::Fixed { .. } => {}
+    }
+}
+
+/// Rapier can't scale time per body, so frozen bodies are slowed down through their velocity.
+///
+/// Overwrites any velocity set during `Update` (ie. by `player_movement`).
+pub fn freeze_velocity(mut frozen_query: Query<(&Frozen, &mut Velocity)>) {
+    for (frozen, mut velocity) in &mut frozen_query {
+        if let Some(Velocity { linvel, angvel }) = frozen.velocity {
+            velocity.linvel = linvel * frozen.time_scale;
+            velocity.angvel = angvel * frozen.time_scale;
+        }
+    }
+}
+
+fn unfreeze_entities(
+    game_time: Res<GameTime>,
+    mut commands: Commands,
+    mut frozen_query: Query<(Entity, &mut Frozen, Option<&mut Velocity>)>,
+) {
+    for (entity, mut frozen, velocity) in &mut frozen_query {
+        frozen.timer.tick(game_time.raw_delta());
+
+        if frozen.timer.finished() {
+            if let (Some(mut velocity), Some(frozen_velocity)) = (velocity, frozen.velocity) {
+                *velocity = frozen_velocity;
+            }
+            commands.entity(entity).remove::<Frozen>();
+        }
+    }
+}
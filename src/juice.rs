@@ -0,0 +1,134 @@
+//! Juice Toggles
+//!
+//! Every effect can be switched on and off at runtime (and its intensity tweaked),
+//! to compare the game "with juice" and "without".
+//!
+//! Juice systems are gated with `.run_if(juice_enabled(JuiceEffect::...))`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    controls::actions::{Action, ActionState},
+    playing,
+};
+
+pub struct JuicePlugin;
+
+impl Plugin for JuicePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JuiceSettings>()
+            .add_systems(Update, toggle_juice.run_if(playing));
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Serialize, Deserialize,
+)]
+pub enum JuiceEffect {
+    Shake,
+    HitStop,
+    Squash,
+    Particles,
+    Flashes,
+    CameraLerp,
+    IdleTempo,
+}
+
+impl JuiceEffect {
+    pub const ALL: [JuiceEffect; 7] = [
+        JuiceEffect::Shake,
+        JuiceEffect::HitStop,
+        JuiceEffect::Squash,
+        JuiceEffect::Particles,
+        JuiceEffect::Flashes,
+        JuiceEffect::CameraLerp,
+        JuiceEffect::IdleTempo,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct JuiceToggle {
+    pub enabled: bool,
+    /// `1.` is the default intensity.
+    pub intensity: f32,
+}
+
+impl Default for JuiceToggle {
+    fn default() -> Self {
+        JuiceToggle {
+            enabled: true,
+            intensity: 1.,
+        }
+    }
+}
+
+#[derive(Debug, Default, Reflect, Resource)]
+#[reflect(Resource)]
+pub struct JuiceSettings {
+    pub shake: JuiceToggle,
+    pub hit_stop: JuiceToggle,
+    pub squash: JuiceToggle,
+    pub particles: JuiceToggle,
+    pub flashes: JuiceToggle,
+    pub camera_lerp: JuiceToggle,
+    /// Randomize the pause between two idle loops.
+    pub idle_tempo: JuiceToggle,
+}
+
+impl JuiceSettings {
+    pub fn get(&self, effect: JuiceEffect) -> &JuiceToggle {
+        match effect {
+            JuiceEffect::Shake => &self.shake,
+            JuiceEffect::HitStop => &self.hit_stop,
+            JuiceEffect::Squash => &self.squash,
+            JuiceEffect::Particles => &self.particles,
+            JuiceEffect::Flashes => &self.flashes,
+            JuiceEffect::CameraLerp => &self.camera_lerp,
+            JuiceEffect::IdleTempo => &self.idle_tempo,
+        }
+    }
+
+    pub fn get_mut(&mut self, effect: JuiceEffect) -> &mut JuiceToggle {
+        match effect {
+            JuiceEffect::Shake => &mut self.shake,
+            JuiceEffect::HitStop => &mut self.hit_stop,
+            JuiceEffect::Squash => &mut self.squash,
+            JuiceEffect::Particles => &mut self.particles,
+            JuiceEffect::Flashes => &mut self.flashes,
+            JuiceEffect::CameraLerp => &mut self.camera_lerp,
+            JuiceEffect::IdleTempo => &mut self.idle_tempo,
+        }
+    }
+
+    /// The intensity of the effect, `0.` if disabled.
+    pub fn intensity(&self, effect: JuiceEffect) -> f32 {
+        let toggle = self.get(effect);
+        if toggle.enabled {
+            toggle.intensity
+        } else {
+            0.
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                   Run If                                   */
+/* -------------------------------------------------------------------------- */
+
+pub fn juice_enabled(effect: JuiceEffect) -> impl FnMut(Res<JuiceSettings>) -> bool + Clone {
+    move |juice_settings: Res<JuiceSettings>| juice_settings.get(effect).enabled
+}
+
+fn toggle_juice(action_state: Res<ActionState>, mut juice_settings: ResMut<JuiceSettings>) {
+    for effect in JuiceEffect::ALL {
+        if action_state.just_pressed(Action::ToggleJuice(effect)) {
+            let toggle = juice_settings.get_mut(effect);
+            toggle.enabled = !toggle.enabled;
+            info!(
+                "{effect:?} juice: {}",
+                if toggle.enabled { "on" } else { "off" }
+            );
+        }
+    }
+}
@@ -6,22 +6,40 @@ use debug::DebugPlugin;
 use std::time::Duration;
 
 use crate::{
-    characters::player::PlayerPlugin, collisions::CollisionsPlugin, controls::Key, map::MapPlugin,
+    camera::{CameraPlugin, CameraShake},
+    characters::CharactersPlugin,
+    collisions::CollisionsPlugin,
+    combat::CombatPlugin,
+    controls::ControlsPlugin,
+    effects::EffectsPlugin,
+    hit_stop::HitStopPlugin,
+    juice::JuicePlugin,
+    map::MapPlugin,
+    replay::ReplayPlugin,
+    ui::UiPlugin,
 };
 
 mod animations;
+mod camera;
 mod characters;
 mod collisions;
+mod combat;
 mod constants;
 mod controls;
 mod debug;
+mod effects;
+mod hit_stop;
+mod juice;
 mod map;
 mod movement;
+mod replay;
+mod ui;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default, Reflect, States)]
 pub enum GameState {
     #[default]
     Playing,
+    Rebinding,
 }
 
 fn main() {
@@ -32,13 +50,6 @@ fn main() {
 
     app.insert_resource(Msaa::Off)
         .insert_resource(ClearColor(Color::rgb(0.5, 0.5, 0.5)))
-        .insert_resource(controls::KeyBindings {
-            up: [Key(KeyCode::W), Key(KeyCode::Z), Key(KeyCode::Up)],
-            down: [Key(KeyCode::S), Key(KeyCode::Down)],
-            right: [Key(KeyCode::D), Key(KeyCode::Right)],
-            left: [Key(KeyCode::A), Key(KeyCode::Q), Key(KeyCode::Left)],
-            interact: [Key(KeyCode::E), Key(KeyCode::R)],
-        })
         .add_plugins((
             DefaultPlugins
                 .set(WindowPlugin {
@@ -59,10 +70,18 @@ fn main() {
             RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(1.),
             // ----- Our plugins -----
             CollisionsPlugin,
+            CombatPlugin,
             DebugPlugin,
             animations::AnimationPlugin,
+            CameraPlugin,
+            CharactersPlugin,
+            ControlsPlugin,
+            EffectsPlugin,
+            HitStopPlugin,
+            JuicePlugin,
             MapPlugin,
-            PlayerPlugin,
+            ReplayPlugin,
+            UiPlugin,
         ))
         .add_state::<GameState>()
         .add_systems(Startup, game_setup);
@@ -85,7 +104,7 @@ fn game_setup(mut commands: Commands, mut rapier_config: ResMut<RapierConfigurat
 
     let mut camera = Camera2dBundle::default();
     camera.projection.scale = 0.1;
-    commands.spawn((camera, PlayerCamera));
+    commands.spawn((camera, PlayerCamera, CameraShake::default()));
 }
 
 /* -------------------------------------------------------------------------- */
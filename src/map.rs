@@ -1,16 +1,17 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::RigidBody;
 
-use crate::{
-    collisions::{TesselatedCollider, TesselatedColliderConfig},
-    GameState,
-};
+use crate::collisions::{TesselatedCollider, TesselatedColliderConfig};
 
 pub struct MapPlugin;
 
+/// Blocks characters and projectiles.
+#[derive(Component)]
+pub struct Wall;
+
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), map_setup);
+        app.add_systems(Startup, map_setup);
     }
 }
 
@@ -148,6 +149,7 @@ fn map_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                                         },
                                     },
                                     Transform::IDENTITY,
+                                    Wall,
                                     Name::new(format!("{name}")),
                                 ));
                             }
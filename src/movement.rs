@@ -3,11 +3,16 @@ use bevy_rapier2d::prelude::Velocity;
 // use bevy_retrograde::prelude::Velocity;
 
 use crate::{
-    animations::sprite_sheet_animation::{AnimationIndices, AnimationTimer, CharacterState},
+    animations::{
+        easing::Easing,
+        sprite_sheet_animation::{AnimationIndices, AnimationTimer, CharacterState},
+    },
     constants::TILE_SIZE,
 };
 
-#[derive(Component, Deref, DerefMut)]
+/// The max speed of a character.
+#[derive(Component, Deref, DerefMut, Reflect)]
+#[reflect(Component)]
 pub struct Speed(pub f32);
 
 impl Default for Speed {
@@ -40,3 +45,209 @@ impl MovementBundle {
         }
     }
 }
+
+/// A character stopped running from (nearly) full speed.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Skidded {
+    pub entity: Entity,
+    /// Before braking.
+    pub velocity: Vec2,
+}
+
+/// How a character ramps up to its [`Speed`] and skids to a stop.
+///
+/// Without it, the velocity is set instantly.
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct MovementFeel {
+    /// Seconds to reach the max speed from a standstill.
+    pub acceleration_time: f32,
+    /// Seconds to stop from the max speed.
+    pub deceleration_time: f32,
+    /// Multiplies the deceleration when going against the current velocity.
+    pub turn_around_boost: f32,
+    pub acceleration_curve: Easing,
+    pub deceleration_curve: Easing,
+}
+
+impl Default for MovementFeel {
+    fn default() -> Self {
+        MovementFeel {
+            acceleration_time: 0.15,
+            deceleration_time: 0.2,
+            turn_around_boost: 2.,
+            acceleration_curve: Easing::QuadraticOut,
+            deceleration_curve: Easing::Linear,
+        }
+    }
+}
+
+impl MovementFeel {
+    /// The velocity after `delta` seconds of moving towards `axis * max_speed`.
+    ///
+    /// The part of the velocity along the input accelerates (or brakes if it goes backward),
+    /// the rest decelerates.
+    pub fn velocity(&self, velocity: Vec2, axis: Vec2, max_speed: f32, delta: f32) -> Vec2 {
+        if max_speed <= 0. {
+            return Vec2::ZERO;
+        }
+
+        let Some(direction) = axis.try_normalize() else {
+            let speed = velocity.length();
+            let speed = self.decelerate(speed, 0., max_speed, delta, 1.);
+            return velocity.normalize_or_zero() * speed;
+        };
+
+        let target_speed = axis.length().min(1.) * max_speed;
+        let along = velocity.dot(direction);
+        let across = velocity - direction * along;
+
+        let along = if along < 0. {
+            -self.decelerate(-along, 0., max_speed, delta, self.turn_around_boost)
+        } else if along < target_speed {
+            self.accelerate(along, target_speed, max_speed, delta)
+        } else {
+            self.decelerate(along, target_speed, max_speed, delta, 1.)
+        };
+        let across_speed = self.decelerate(
+            across.length(),
+            0.,
+            max_speed,
+            delta,
+            self.turn_around_boost,
+        );
+
+        direction * along + across.normalize_or_zero() * across_speed
+    }
+
+    /// Move forward on the acceleration curve, from the point matching the current `speed`.
+    fn accelerate(&self, speed: f32, target_speed: f32, max_speed: f32, delta: f32) -> f32 {
+        if self.acceleration_time <= 0. {
+            return target_speed;
+        }
+
+        let curve = self.acceleration_curve;
+        let t = curve.inverse(speed / max_speed) + delta / self.acceleration_time;
+        (curve.ease(t.min(1.)) * max_speed).min(target_speed)
+    }
+
+    /// Move forward on the deceleration curve (going from the max speed to 0).
+    fn decelerate(
+        &self,
+        speed: f32,
+        target_speed: f32,
+        max_speed: f32,
+        delta: f32,
+        boost: f32,
+    ) -> f32 {
+        let duration = self.deceleration_time / boost.max(f32::EPSILON);
+        if duration <= 0. {
+            return target_speed;
+        }
+
+        // ie. pushed by a knockback: brake linearly down to the max speed
+        if speed > max_speed {
+            return (speed - max_speed * delta / duration).max(target_speed);
+        }
+
+        let curve = self.deceleration_curve;
+        let t = curve.inverse(1. - speed / max_speed) + delta / duration;
+        ((1. - curve.ease(t.min(1.))) * max_speed).max(target_speed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_SPEED: f32 = 100.;
+
+    fn assert_close(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 0.01, "{value} != {expected}");
+    }
+
+    #[test]
+    fn accelerates_along_the_curve() {
+        let feel = MovementFeel::default();
+        let half = feel.acceleration_time / 2.;
+
+        let velocity = feel.velocity(Vec2::ZERO, Vec2::X, MAX_SPEED, half);
+        assert_close(velocity.x, feel.acceleration_curve.ease(0.5) * MAX_SPEED);
+        assert_eq!(velocity.y, 0.);
+
+        // resumes from the point of the curve matching the current speed
+        let velocity = feel.velocity(velocity, Vec2::X, MAX_SPEED, half);
+        assert_close(velocity.x, MAX_SPEED);
+
+        let velocity = feel.velocity(velocity, Vec2::X, MAX_SPEED, half);
+        assert_close(velocity.x, MAX_SPEED);
+    }
+
+    #[test]
+    fn half_tilted_stick_reaches_half_speed() {
+        let feel = MovementFeel::default();
+
+        let velocity = feel.velocity(Vec2::ZERO, Vec2::X * 0.5, MAX_SPEED, 1.);
+        assert_close(velocity.x, MAX_SPEED / 2.);
+    }
+
+    #[test]
+    fn brakes_to_a_stop() {
+        let feel = MovementFeel {
+            deceleration_curve: Easing::Linear,
+            ..default()
+        };
+        let quarter = feel.deceleration_time / 4.;
+
+        let mut velocity = Vec2::X * MAX_SPEED;
+        let mut speeds = Vec::new();
+        for _ in 0..4 {
+            velocity = feel.velocity(velocity, Vec2::ZERO, MAX_SPEED, quarter);
+            speeds.push(velocity.x);
+        }
+
+        for (speed, expected) in speeds.into_iter().zip([75., 50., 25., 0.]) {
+            assert_close(speed, expected);
+        }
+        assert_eq!(velocity.y, 0.);
+    }
+
+    #[test]
+    fn turning_around_brakes_faster() {
+        let feel = MovementFeel {
+            deceleration_curve: Easing::Linear,
+            ..default()
+        };
+        let quarter = feel.deceleration_time / 4.;
+        let velocity = Vec2::X * MAX_SPEED;
+
+        let released = feel.velocity(velocity, Vec2::ZERO, MAX_SPEED, quarter);
+        let turned_around = feel.velocity(velocity, Vec2::NEG_X, MAX_SPEED, quarter);
+
+        assert_close(released.x, 75.);
+        assert_close(turned_around.x, 100. - 25. * feel.turn_around_boost);
+    }
+
+    #[test]
+    fn knockback_brakes_down_to_the_max_speed() {
+        let feel = MovementFeel::default();
+        let quarter = feel.deceleration_time / 4.;
+
+        let velocity = feel.velocity(Vec2::X * MAX_SPEED * 2., Vec2::ZERO, MAX_SPEED, quarter);
+        assert_close(velocity.x, MAX_SPEED * 2. - MAX_SPEED / 4.);
+    }
+
+    #[test]
+    fn easing_inverse_round_trips() {
+        for easing in Easing::ALL {
+            for step in 0..=10 {
+                let value = step as f32 / 10.;
+                let round_trip = easing.ease(easing.inverse(value));
+                assert!(
+                    (round_trip - value).abs() < 1e-3,
+                    "{easing:?}: {value} became {round_trip}"
+                );
+            }
+        }
+    }
+}
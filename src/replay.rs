@@ -0,0 +1,467 @@
+//! Replay
+//!
+//! Record the [`ActionState`] of every frame to reproduce a session:
+//!
+//! - `cargo run -- --record session.ron` writes the inputs every [`RECORDING_SAVE_PERIOD`],
+//!   when the rebinding menu opens and when the game is closed,
+//! - `cargo run -- --replay session.ron` plays them back instead of the real inputs.
+//!
+//! A session can't be recorded while replaying another.
+//!
+//! Both run on a [fixed step](GameTime::fixed_step) (physics included)
+//! and seed the [`GameRng`] and [`EffectsRng`] with the recorded seed,
+//! so the run is the same frame by frame.
+//! The mouse aims through the cursor rather than an action: the [`Aim`] is recorded as well.
+//! The player's position and sprite index are recorded too:
+//! the replay warns at the first frame where they differ.
+
+use bevy::{app::AppExit, prelude::*};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    characters::player::Player,
+    combat::aim::{update_aim, Aim, AimSet},
+    controls::{
+        actions::{update_action_state, Action, ActionState},
+        buffer::buffer_actions,
+    },
+    hit_stop::GameTime,
+    GameState,
+};
+
+/// The duration of a frame while recording or replaying.
+pub const REPLAY_FIXED_STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+/// In real time, not to lose a whole session if the game crashes.
+pub const RECORDING_SAVE_PERIOD: Duration = Duration::from_secs(10);
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let replay_args = ReplayArgs::parse(std::env::args().skip(1)).unwrap_or_else(|error| {
+            error!("{error}: neither recording nor replaying");
+            ReplayArgs::default()
+        });
+
+        let mut seed = rand::random();
+        if let Some(path) = replay_args.replay {
+            match Recording::load(&path) {
+                Ok(recording) => {
+                    seed = recording.seed;
+                    app.insert_resource(Replayer {
+                        recording,
+                        frame: 0,
+                        diverged: false,
+                        action_state: ActionState::default(),
+                    });
+                }
+                Err(error) => error!("Could not load the replay {}: {error}", path.display()),
+            }
+        }
+        // with the seed of the session
+        if let Some(path) = replay_args.record {
+            app.insert_resource(Recorder {
+                path,
+                recording: Recording::new(seed),
+                save_timer: Timer::new(RECORDING_SAVE_PERIOD, TimerMode::Repeating),
+            });
+        }
+
+        app.insert_resource(GameRng::new(seed))
+            .insert_resource(EffectsRng::new(seed))
+            .add_systems(Startup, use_fixed_step)
+            .add_systems(
+                PreUpdate,
+                (
+                    replay_actions.run_if(resource_exists::<Replayer>()),
+                    record_actions.run_if(resource_exists::<Recorder>()),
+                )
+                    .chain()
+                    .after(update_action_state)
+                    .before(buffer_actions),
+            )
+            .add_systems(
+                Update,
+                (
+                    replay_aim
+                        .run_if(resource_exists::<Replayer>())
+                        .in_set(AimSet)
+                        .after(update_aim),
+                    record_aim
+                        .run_if(resource_exists::<Recorder>())
+                        .after(AimSet),
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::Playing),
+                save_recording.run_if(resource_exists::<Recorder>()),
+            )
+            .add_systems(
+                Last,
+                (
+                    (
+                        record_player,
+                        save_recording_periodically,
+                        save_recording_on_exit,
+                    )
+                        .chain()
+                        .run_if(resource_exists::<Recorder>()),
+                    check_replay.run_if(resource_exists::<Replayer>()),
+                ),
+            );
+    }
+}
+
+/// The command line options, ie. `--record session.ron`.
+#[derive(Debug, Default, PartialEq)]
+struct ReplayArgs {
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+enum ReplayArgsError {
+    MissingPath(String),
+    RecordAndReplay,
+}
+
+impl fmt::Display for ReplayArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayArgsError::MissingPath(flag) => write!(f, "`{flag}` expects a path"),
+            ReplayArgsError::RecordAndReplay => {
+                write!(f, "`--record` and `--replay` can't be used together")
+            }
+        }
+    }
+}
+
+impl ReplayArgs {
+    /// The other arguments are ignored.
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ReplayArgsError> {
+        let mut args = args.into_iter();
+        let mut replay_args = ReplayArgs::default();
+        while let Some(arg) = args.next() {
+            let path = match arg.as_str() {
+                "--record" => &mut replay_args.record,
+                "--replay" => &mut replay_args.replay,
+                _ => continue,
+            };
+            *path = Some(args.next().ok_or(ReplayArgsError::MissingPath(arg))?.into());
+        }
+
+        if replay_args.record.is_some() && replay_args.replay.is_some() {
+            return Err(ReplayArgsError::RecordAndReplay);
+        }
+        Ok(replay_args)
+    }
+}
+
+/// The random number generator of gameplay systems, seeded to be replayed.
+#[derive(Deref, DerefMut, Resource)]
+pub struct GameRng(StdRng);
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+/// The random number generator of visual effects, apart from the [`GameRng`]:
+/// toggling an effect never changes the gameplay.
+#[derive(Deref, DerefMut, Resource)]
+pub struct EffectsRng(StdRng);
+
+impl EffectsRng {
+    pub fn new(seed: u64) -> Self {
+        EffectsRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Recording {
+    pub seed: u64,
+    pub fixed_step: Duration,
+    pub frames: Vec<RecordedFrame>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// The value of each action, see [`ActionState::value`].
+    pub actions: BTreeMap<Action, f32>,
+    pub aim: Option<(f32, f32)>,
+    /// The player at the end of the frame.
+    pub player: Option<PlayerSnapshot>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub translation: (f32, f32),
+    pub sprite_index: usize,
+}
+
+impl Recording {
+    fn new(seed: u64) -> Self {
+        Recording {
+            seed,
+            fixed_step: REPLAY_FIXED_STEP,
+            frames: Vec::new(),
+        }
+    }
+
+    fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(ron::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, ron::ser::to_string_pretty(self, default())?)?;
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+pub struct Recorder {
+    path: PathBuf,
+    recording: Recording,
+    save_timer: Timer,
+}
+
+impl Recorder {
+    /// Overwrites the previous save. Returns `false` on failure, after logging the error.
+    fn save(&self) -> bool {
+        match self.recording.write(&self.path) {
+            Ok(()) => true,
+            Err(error) => {
+                error!(
+                    "Could not save the recording to {}: {error}",
+                    self.path.display()
+                );
+                false
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Replayer {
+    recording: Recording,
+    /// The next frame to play.
+    frame: usize,
+    /// Only warn at the first difference.
+    diverged: bool,
+    /// The replayed actions, apart from the real inputs read before them each frame.
+    action_state: ActionState,
+}
+
+fn use_fixed_step(
+    recorder: Option<Res<Recorder>>,
+    replayer: Option<Res<Replayer>>,
+    mut game_time: ResMut<GameTime>,
+) {
+    let fixed_step = match (recorder, replayer) {
+        (_, Some(replayer)) => replayer.recording.fixed_step,
+        (Some(recorder), None) => recorder.recording.fixed_step,
+        (None, None) => return,
+    };
+    game_time.set_fixed_step(Some(fixed_step));
+}
+
+/// Override the real inputs with the recorded ones.
+///
+/// The replayed state is updated from the previous replayed frame, not from the real inputs:
+/// a held action is only just pressed on its first frame.
+fn replay_actions(mut replayer: ResMut<Replayer>, mut action_state: ResMut<ActionState>) {
+    let Replayer {
+        recording,
+        frame,
+        action_state: replayed_state,
+        ..
+    } = &mut *replayer;
+    if let Some(frame) = recording.frames.get(*frame) {
+        replayed_state.update(
+            frame
+                .actions
+                .iter()
+                .map(|(action, value)| (*action, *value))
+                .collect(),
+        );
+        action_state.clone_from(replayed_state);
+    }
+}
+
+fn record_actions(action_state: Res<ActionState>, mut recorder: ResMut<Recorder>) {
+    recorder.recording.frames.push(RecordedFrame {
+        actions: action_state.values().collect(),
+        aim: None,
+        player: None,
+    });
+}
+
+fn replay_aim(replayer: Res<Replayer>, mut aim_query: Query<&mut Aim, With<Player>>) {
+    let Some((x, y)) = replayer
+        .recording
+        .frames
+        .get(replayer.frame)
+        .and_then(|frame| frame.aim)
+    else {
+        return;
+    };
+    if let Ok(mut aim) = aim_query.get_single_mut() {
+        **aim = Vec2::new(x, y);
+    }
+}
+
+fn record_aim(aim_query: Query<&Aim, With<Player>>, mut recorder: ResMut<Recorder>) {
+    let aim = aim_query.get_single().ok().map(|aim| (aim.x, aim.y));
+    if let Some(frame) = recorder.recording.frames.last_mut() {
+        frame.aim = aim;
+    }
+}
+
+fn player_snapshot(
+    player_query: &Query<(&Transform, &TextureAtlasSprite), With<Player>>,
+) -> Option<PlayerSnapshot> {
+    let (transform, sprite) = player_query.get_single().ok()?;
+    Some(PlayerSnapshot {
+        translation: (transform.translation.x, transform.translation.y),
+        sprite_index: sprite.index,
+    })
+}
+
+fn record_player(
+    player_query: Query<(&Transform, &TextureAtlasSprite), With<Player>>,
+    mut recorder: ResMut<Recorder>,
+) {
+    let player = player_snapshot(&player_query);
+    if let Some(frame) = recorder.recording.frames.last_mut() {
+        frame.player = player;
+    }
+}
+
+fn save_recording(recorder: Res<Recorder>) {
+    recorder.save();
+}
+
+fn save_recording_periodically(time: Res<Time>, mut recorder: ResMut<Recorder>) {
+    if recorder.save_timer.tick(time.delta()).just_finished() {
+        recorder.save();
+    }
+}
+
+fn save_recording_on_exit(mut exit_events: EventReader<AppExit>, recorder: Res<Recorder>) {
+    if exit_events.iter().next().is_some() && recorder.save() {
+        info!(
+            "{} frames recorded to {}",
+            recorder.recording.frames.len(),
+            recorder.path.display()
+        );
+    }
+}
+
+/// Compare the player with the recorded one, and give the control back at the end.
+fn check_replay(
+    mut commands: Commands,
+    player_query: Query<(&Transform, &TextureAtlasSprite), With<Player>>,
+    mut replayer: ResMut<Replayer>,
+    mut game_time: ResMut<GameTime>,
+) {
+    let Some(frame) = replayer.recording.frames.get(replayer.frame) else {
+        info!("Replay finished");
+        game_time.set_fixed_step(None);
+        commands.remove_resource::<Replayer>();
+        return;
+    };
+
+    let player = player_snapshot(&player_query);
+    if player != frame.player && !replayer.diverged {
+        warn!(
+            "Replay diverged at frame {}: recorded {:?}, got {player:?}",
+            replayer.frame, frame.player
+        );
+        replayer.diverged = true;
+    }
+    replayer.frame += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+
+    fn held_frame(action: Action) -> RecordedFrame {
+        RecordedFrame {
+            actions: BTreeMap::from([(action, 1.)]),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn held_action_is_just_pressed_once() {
+        let mut app = App::new();
+        app.init_resource::<ActionState>()
+            .insert_resource(Replayer {
+                recording: Recording {
+                    frames: (0..4).map(|_| held_frame(Action::Shoot)).collect(),
+                    ..Recording::new(0)
+                },
+                frame: 0,
+                diverged: false,
+                action_state: ActionState::default(),
+            })
+            .add_systems(
+                PreUpdate,
+                (
+                    // no real input
+                    |mut action_state: ResMut<ActionState>| action_state.update(HashMap::default()),
+                    replay_actions,
+                )
+                    .chain(),
+            );
+
+        let mut just_pressed = Vec::new();
+        for _ in 0..4 {
+            app.update();
+            let action_state = app.world.resource::<ActionState>();
+            assert!(action_state.pressed(Action::Shoot));
+            just_pressed.push(action_state.just_pressed(Action::Shoot));
+            app.world.resource_mut::<Replayer>().frame += 1;
+        }
+
+        assert_eq!(just_pressed, [true, false, false, false]);
+    }
+
+    fn args(args: &[&str]) -> Result<ReplayArgs, ReplayArgsError> {
+        ReplayArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn unknown_flag_is_skipped_alone() {
+        assert_eq!(
+            args(&["--fullscreen", "--record", "session.ron"]),
+            Ok(ReplayArgs {
+                record: Some("session.ron".into()),
+                replay: None,
+            })
+        );
+    }
+
+    #[test]
+    fn record_and_replay_are_rejected_together() {
+        assert_eq!(
+            args(&["--record", "a.ron", "--replay", "b.ron"]),
+            Err(ReplayArgsError::RecordAndReplay)
+        );
+        assert_eq!(
+            args(&["--replay"]),
+            Err(ReplayArgsError::MissingPath("--replay".into()))
+        );
+    }
+}
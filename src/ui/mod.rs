@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+
+pub mod rebinding_menu;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(rebinding_menu::RebindingMenuPlugin);
+    }
+}
@@ -0,0 +1,372 @@
+//! Rebinding Menu
+//!
+//! Lists every [`Action`] with its bindings.
+//! Click on an action then press a key/button, click (or push a stick) to bind it.
+//! Bindings shared by several actions are highlighted.
+//!
+//! Changes are persisted to the config file by the controls plugin.
+
+use bevy::prelude::*;
+
+use crate::{
+    controls::{
+        actions::{Action, ActionState, PRESS_THRESHOLD},
+        gamepad::{ActiveGamepad, LastInputDevice},
+        AxisDirection, Binding, Key, KeyBindings,
+    },
+    GameState,
+};
+
+const BACKGROUND_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.9);
+const BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+const HOVERED_BUTTON_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+const TEXT_COLOR: Color = Color::WHITE;
+const CAPTURE_COLOR: Color = Color::YELLOW;
+const CONFLICT_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+
+pub struct RebindingMenuPlugin;
+
+impl Plugin for RebindingMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RebindingCapture>()
+            .add_systems(OnEnter(GameState::Rebinding), spawn_rebinding_menu)
+            .add_systems(
+                OnExit(GameState::Rebinding),
+                (despawn_rebinding_menu, cancel_capture),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_rebinding_menu,
+                    (
+                        menu_buttons,
+                        capture_binding,
+                        update_bindings_texts,
+                        update_hint_text,
+                    )
+                        .chain()
+                        .run_if(in_state(GameState::Rebinding)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The action waiting for a new binding.
+#[derive(Debug, Default, Deref, DerefMut, Resource)]
+pub struct RebindingCapture(pub Option<Action>);
+
+#[derive(Component)]
+struct RebindingMenu;
+
+#[derive(Component)]
+enum MenuButton {
+    /// Capture a new binding for this action.
+    Bind(Action),
+    Clear(Action),
+    ResetToDefaults,
+    Back,
+}
+
+/// Lists the bindings of an action.
+#[derive(Component)]
+struct BindingsText(Action);
+
+#[derive(Component)]
+struct HintText;
+
+fn toggle_rebinding_menu(
+    action_state: Res<ActionState>,
+    capture: Res<RebindingCapture>,
+    game_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // while capturing, the menu key cancels the capture instead
+    if !action_state.just_pressed(Action::Menu) || capture.is_some() {
+        return;
+    }
+
+    match game_state.get() {
+        GameState::Playing => next_state.set(GameState::Rebinding),
+        GameState::Rebinding => next_state.set(GameState::Playing),
+    }
+}
+
+fn spawn_rebinding_menu(mut commands: Commands) {
+    let text_style = |font_size| TextStyle {
+        font_size,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let button = || ButtonBundle {
+        style: Style {
+            padding: UiRect::axes(Val::Px(8.), Val::Px(4.)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        background_color: BUTTON_COLOR.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                ..default()
+            },
+            RebindingMenu,
+            Name::new("Rebinding Menu"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Key Bindings", text_style(40.)));
+
+            // Every action has a default binding
+            for action in KeyBindings::default().keys() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(900.),
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(8.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(
+                            TextBundle::from_section(format!("{action:?}"), text_style(20.))
+                                .with_style(Style {
+                                    width: Val::Px(220.),
+                                    ..default()
+                                }),
+                        );
+                        row.spawn((
+                            TextBundle::from_section("", text_style(20.)).with_style(Style {
+                                flex_grow: 1.,
+                                ..default()
+                            }),
+                            BindingsText(*action),
+                        ));
+                        row.spawn((button(), MenuButton::Bind(*action)))
+                            .with_children(|button| {
+                                button.spawn(TextBundle::from_section("Bind", text_style(20.)));
+                            });
+                        row.spawn((button(), MenuButton::Clear(*action)))
+                            .with_children(|button| {
+                                button.spawn(TextBundle::from_section("Clear", text_style(20.)));
+                            });
+                    });
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(16.),
+                        margin: UiRect::top(Val::Px(16.)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|buttons| {
+                    buttons
+                        .spawn((button(), MenuButton::ResetToDefaults))
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section(
+                                "Reset to defaults",
+                                text_style(20.),
+                            ));
+                        });
+                    buttons
+                        .spawn((button(), MenuButton::Back))
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section("Back", text_style(20.)));
+                        });
+                });
+
+            parent.spawn((TextBundle::from_section("", text_style(16.)), HintText));
+        });
+}
+
+fn despawn_rebinding_menu(mut commands: Commands, menu_query: Query<Entity, With<RebindingMenu>>) {
+    for menu in &menu_query {
+        commands.entity(menu).despawn_recursive();
+    }
+}
+
+fn cancel_capture(mut capture: ResMut<RebindingCapture>) {
+    **capture = None;
+}
+
+fn menu_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &MenuButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut capture: ResMut<RebindingCapture>,
+    mut key_bindings: ResMut<KeyBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, menu_button, mut background_color) in &mut interaction_query {
+        match interaction {
+            Interaction::Pressed => match menu_button {
+                MenuButton::Bind(action) => **capture = Some(*action),
+                MenuButton::Clear(action) => {
+                    if let Some(bindings) = key_bindings.get_mut(action) {
+                        bindings.clear();
+                    }
+                }
+                MenuButton::ResetToDefaults => *key_bindings = KeyBindings::default(),
+                MenuButton::Back => next_state.set(GameState::Playing),
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+/// Bind the first key, mouse button, gamepad button or stick direction pressed to the captured action.
+///
+/// `Escape` cancels the capture.
+fn capture_binding(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    active_gamepad: Res<ActiveGamepad>,
+    mut capture: ResMut<RebindingCapture>,
+    mut key_bindings: ResMut<KeyBindings>,
+) {
+    let Some(action) = **capture else {
+        return;
+    };
+    // the click on the Bind button is not a binding
+    if capture.is_changed() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        **capture = None;
+        return;
+    }
+
+    let gamepad_axis = active_gamepad.and_then(|gamepad| {
+        gamepad_axes
+            .devices()
+            .filter(|axis| axis.gamepad == gamepad)
+            .find_map(|axis| {
+                let value = gamepad_axes.get(*axis)?;
+                (value.abs() >= PRESS_THRESHOLD).then(|| {
+                    let direction = if value > 0. {
+                        AxisDirection::Positive
+                    } else {
+                        AxisDirection::Negative
+                    };
+                    Binding::GamepadAxis(axis.axis_type, direction)
+                })
+            })
+    });
+
+    let binding = keyboard_input
+        .get_just_pressed()
+        .next()
+        .map(|key_code| Binding::Key(Key(*key_code)))
+        .or_else(|| {
+            mouse_buttons
+                .get_just_pressed()
+                .next()
+                .map(|mouse_button| Binding::MouseButton(*mouse_button))
+        })
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::GamepadButton(button.button_type))
+        })
+        .or(gamepad_axis);
+
+    if let Some(binding) = binding {
+        let bindings = key_bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+
+        let conflicts = key_bindings.actions_bound_to(binding);
+        if conflicts.len() > 1 {
+            warn!("{} is bound to {conflicts:?}", binding.name());
+        }
+        **capture = None;
+    }
+}
+
+fn update_bindings_texts(
+    key_bindings: Res<KeyBindings>,
+    capture: Res<RebindingCapture>,
+    new_menu_query: Query<(), Added<BindingsText>>,
+    mut bindings_text_query: Query<(&mut Text, &BindingsText)>,
+) {
+    if !key_bindings.is_changed() && !capture.is_changed() && new_menu_query.is_empty() {
+        return;
+    }
+
+    for (mut text, BindingsText(action)) in &mut bindings_text_query {
+        let bindings = key_bindings.get(action).cloned().unwrap_or_default();
+        let conflict = bindings
+            .iter()
+            .any(|binding| key_bindings.actions_bound_to(*binding).len() > 1);
+
+        let section = &mut text.sections[0];
+        if **capture == Some(*action) {
+            section.value =
+                "Press a key, click, press a button or push a stick... (Escape to cancel)".into();
+            section.style.color = CAPTURE_COLOR;
+        } else {
+            section.value = bindings
+                .iter()
+                .map(|binding| {
+                    let conflicts = key_bindings.actions_bound_to(*binding);
+                    if conflicts.len() > 1 {
+                        let others = conflicts
+                            .iter()
+                            .filter(|other| *other != action)
+                            .map(|other| format!("{other:?}"))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        format!("{} (also {others})", binding.name())
+                    } else {
+                        binding.name()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            section.style.color = if conflict { CONFLICT_COLOR } else { TEXT_COLOR };
+        }
+    }
+}
+
+/// Show how to leave the menu with the device used last.
+fn update_hint_text(
+    key_bindings: Res<KeyBindings>,
+    last_input_device: Res<LastInputDevice>,
+    new_menu_query: Query<(), Added<HintText>>,
+    mut hint_text_query: Query<&mut Text, With<HintText>>,
+) {
+    if !key_bindings.is_changed() && !last_input_device.is_changed() && new_menu_query.is_empty() {
+        return;
+    }
+
+    if let Ok(mut text) = hint_text_query.get_single_mut() {
+        text.sections[0].value = match key_bindings.prompt(Action::Menu, *last_input_device) {
+            Some(binding) => format!("[{}] Back", binding.name()),
+            None => String::new(),
+        };
+    }
+}